@@ -0,0 +1,508 @@
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::context::CombatContext;
+use crate::levels::Levels;
+use crate::player::{
+    AttackPotion, AttackPrayer, AttackType, MagicPotion, MagicPrayer, RangedPotion, RangedPrayer,
+    StrengthPotion, StrengthPrayer,
+};
+
+/// How a potion boost wears off after the dose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoostDecay {
+    /// Loses a level every minute until back at base level.
+    LINEAR,
+    /// Stays at the full boost for this many minutes, then drops straight back to base.
+    HELD(isize),
+}
+
+impl BoostDecay {
+    /// Levels left of a `boost` drunk `minutes` ago. Drains restore at the same rate.
+    pub fn remaining(&self, boost: isize, minutes: isize) -> isize {
+        match self {
+            BoostDecay::LINEAR if boost >= 0 => (boost - minutes).max(0),
+            BoostDecay::LINEAR => (boost + minutes).min(0),
+            BoostDecay::HELD(duration) if minutes < *duration => boost,
+            BoostDecay::HELD(_) => 0,
+        }
+    }
+}
+
+/// Divine potions and overloads hold their boost for five minutes.
+const HELD_MINUTES: isize = 5;
+/// Smelling salts keep re-boosting for a shorter time than overloads.
+const SMELLING_SALTS_MINUTES: isize = 2;
+
+impl AttackPotion {
+    pub fn decay(&self) -> BoostDecay {
+        match self {
+            AttackPotion::DIVINESUPERATTACK | AttackPotion::OVERLOAD => {
+                BoostDecay::HELD(HELD_MINUTES)
+            }
+            AttackPotion::SMELLINGSALTS => BoostDecay::HELD(SMELLING_SALTS_MINUTES),
+            _ => BoostDecay::LINEAR,
+        }
+    }
+}
+
+impl StrengthPotion {
+    pub fn decay(&self) -> BoostDecay {
+        match self {
+            StrengthPotion::DIVINESUPERSTRENGTH | StrengthPotion::OVERLOAD => {
+                BoostDecay::HELD(HELD_MINUTES)
+            }
+            StrengthPotion::SMELLINGSALTS => BoostDecay::HELD(SMELLING_SALTS_MINUTES),
+            _ => BoostDecay::LINEAR,
+        }
+    }
+}
+
+impl RangedPotion {
+    pub fn decay(&self) -> BoostDecay {
+        match self {
+            RangedPotion::DIVINERANGING | RangedPotion::OVERLOAD => BoostDecay::HELD(HELD_MINUTES),
+            RangedPotion::SMELLINGSALTS => BoostDecay::HELD(SMELLING_SALTS_MINUTES),
+            _ => BoostDecay::LINEAR,
+        }
+    }
+}
+
+impl MagicPotion {
+    pub fn decay(&self) -> BoostDecay {
+        match self {
+            MagicPotion::DIVINEMAGIC | MagicPotion::OVERLOAD => BoostDecay::HELD(HELD_MINUTES),
+            MagicPotion::SMELLINGSALTS => BoostDecay::HELD(SMELLING_SALTS_MINUTES),
+            _ => BoostDecay::LINEAR,
+        }
+    }
+}
+
+/// Length of a trip and how often potions are re-dosed during it.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct Trip {
+    pub minutes: isize,
+    /// Minutes between doses, or `None` to only drink once at the start.
+    pub redose_minutes: Option<isize>,
+}
+
+impl Default for Trip {
+    fn default() -> Self {
+        Trip {
+            minutes: 60,
+            redose_minutes: None,
+        }
+    }
+}
+
+impl Trip {
+    /// Minutes since the last dose for every minute of the trip.
+    pub fn dose_ages(&self) -> impl Iterator<Item = isize> + '_ {
+        (0..self.minutes.max(1)).map(|minute| match self.redose_minutes {
+            Some(redose) if redose > 0 => minute % redose,
+            _ => minute,
+        })
+    }
+}
+
+/// Every prayer and potion choice that raises the player's offensive levels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Boosts {
+    pub attack_potion: AttackPotion,
+    pub attack_prayer: AttackPrayer,
+    pub strength_potion: StrengthPotion,
+    pub strength_prayer: StrengthPrayer,
+    pub ranged_potion: RangedPotion,
+    pub ranged_prayer: RangedPrayer,
+    pub magic_potion: MagicPotion,
+    pub magic_prayer: MagicPrayer,
+}
+
+impl fmt::Display for Boosts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let choices: Vec<String> = self.choices().into_iter().map(|x| x.0).collect();
+        if choices.is_empty() {
+            write!(f, "NONE")
+        } else {
+            write!(f, "{}", choices.join(", "))
+        }
+    }
+}
+
+impl Boosts {
+    /// Every choice that is not `NONE`, each paired with the boosts as they would be without it.
+    pub fn choices(&self) -> Vec<(String, Boosts)> {
+        let mut choices = Vec::new();
+        if self.attack_potion != AttackPotion::NONE {
+            let without = Boosts {
+                attack_potion: AttackPotion::NONE,
+                ..*self
+            };
+            choices.push((format!("{:?} (attack)", self.attack_potion), without));
+        }
+        if self.attack_prayer != AttackPrayer::NONE {
+            let without = Boosts {
+                attack_prayer: AttackPrayer::NONE,
+                ..*self
+            };
+            choices.push((format!("{:?} (attack)", self.attack_prayer), without));
+        }
+        if self.strength_potion != StrengthPotion::NONE {
+            let without = Boosts {
+                strength_potion: StrengthPotion::NONE,
+                ..*self
+            };
+            choices.push((format!("{:?} (strength)", self.strength_potion), without));
+        }
+        if self.strength_prayer != StrengthPrayer::NONE {
+            let without = Boosts {
+                strength_prayer: StrengthPrayer::NONE,
+                ..*self
+            };
+            choices.push((format!("{:?} (strength)", self.strength_prayer), without));
+        }
+        if self.ranged_potion != RangedPotion::NONE {
+            let without = Boosts {
+                ranged_potion: RangedPotion::NONE,
+                ..*self
+            };
+            choices.push((format!("{:?} (ranged)", self.ranged_potion), without));
+        }
+        if self.ranged_prayer != RangedPrayer::NONE {
+            let without = Boosts {
+                ranged_prayer: RangedPrayer::NONE,
+                ..*self
+            };
+            choices.push((format!("{:?} (ranged)", self.ranged_prayer), without));
+        }
+        if self.magic_potion != MagicPotion::NONE {
+            let without = Boosts {
+                magic_potion: MagicPotion::NONE,
+                ..*self
+            };
+            choices.push((format!("{:?} (magic)", self.magic_potion), without));
+        }
+        if self.magic_prayer != MagicPrayer::NONE {
+            let without = Boosts {
+                magic_prayer: MagicPrayer::NONE,
+                ..*self
+            };
+            choices.push((format!("{:?} (magic)", self.magic_prayer), without));
+        }
+        choices
+    }
+}
+
+/// Potions and other boosts the player can bring along from the bank.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Potion {
+    ATTACK,
+    STRENGTH,
+    COMBAT,
+    SUPERATTACK,
+    SUPERSTRENGTH,
+    SUPERCOMBAT,
+    DIVINESUPERATTACK,
+    DIVINESUPERSTRENGTH,
+    DIVINESUPERCOMBAT,
+    RANGING,
+    SUPERRANGING,
+    DIVINERANGING,
+    MAGIC,
+    SUPERMAGIC,
+    DIVINEMAGIC,
+    OVERLOAD,
+    SMELLINGSALTS,
+    ZAMORAKBREW,
+    DRAGONBATTLEAXE,
+    IMBUEDHEART,
+    SATURATEDHEART,
+}
+
+impl Potion {
+    /// Overloads can only be made inside Chambers of Xeric and smelling salts are only
+    /// handed out in the Tombs of Amascut.
+    pub fn available(&self, context: &CombatContext) -> bool {
+        match self {
+            Potion::OVERLOAD => context.in_chambers(),
+            Potion::SMELLINGSALTS => context.in_tombs(),
+            _ => true,
+        }
+    }
+
+    fn attack(&self) -> Option<AttackPotion> {
+        match self {
+            Potion::ATTACK => Some(AttackPotion::ATTACK),
+            Potion::COMBAT => Some(AttackPotion::COMBAT),
+            Potion::SUPERATTACK | Potion::SUPERCOMBAT => Some(AttackPotion::SUPERATTACK),
+            Potion::DIVINESUPERATTACK | Potion::DIVINESUPERCOMBAT => {
+                Some(AttackPotion::DIVINESUPERATTACK)
+            }
+            Potion::OVERLOAD => Some(AttackPotion::OVERLOAD),
+            Potion::SMELLINGSALTS => Some(AttackPotion::SMELLINGSALTS),
+            Potion::ZAMORAKBREW => Some(AttackPotion::ZAMORAKBREW),
+            _ => None,
+        }
+    }
+
+    fn strength(&self) -> Option<StrengthPotion> {
+        match self {
+            Potion::STRENGTH => Some(StrengthPotion::STRENGTH),
+            Potion::COMBAT => Some(StrengthPotion::COMBAT),
+            Potion::SUPERSTRENGTH | Potion::SUPERCOMBAT => Some(StrengthPotion::SUPERSTRENGTH),
+            Potion::DIVINESUPERSTRENGTH | Potion::DIVINESUPERCOMBAT => {
+                Some(StrengthPotion::DIVINESUPERSTRENGTH)
+            }
+            Potion::OVERLOAD => Some(StrengthPotion::OVERLOAD),
+            Potion::SMELLINGSALTS => Some(StrengthPotion::SMELLINGSALTS),
+            Potion::ZAMORAKBREW => Some(StrengthPotion::ZAMORAKBREW),
+            Potion::DRAGONBATTLEAXE => Some(StrengthPotion::DRAGONBATTLEAXE),
+            _ => None,
+        }
+    }
+
+    fn ranged(&self) -> Option<RangedPotion> {
+        match self {
+            Potion::RANGING => Some(RangedPotion::RANGING),
+            Potion::SUPERRANGING => Some(RangedPotion::SUPERRANGING),
+            Potion::DIVINERANGING => Some(RangedPotion::DIVINERANGING),
+            Potion::OVERLOAD => Some(RangedPotion::OVERLOAD),
+            Potion::SMELLINGSALTS => Some(RangedPotion::SMELLINGSALTS),
+            _ => None,
+        }
+    }
+
+    fn magic(&self) -> Option<MagicPotion> {
+        match self {
+            Potion::MAGIC => Some(MagicPotion::MAGIC),
+            Potion::SUPERMAGIC => Some(MagicPotion::SUPERMAGIC),
+            Potion::DIVINEMAGIC => Some(MagicPotion::DIVINEMAGIC),
+            Potion::OVERLOAD => Some(MagicPotion::OVERLOAD),
+            Potion::SMELLINGSALTS => Some(MagicPotion::SMELLINGSALTS),
+            Potion::IMBUEDHEART => Some(MagicPotion::IMBUEDHEART),
+            Potion::SATURATEDHEART => Some(MagicPotion::SATURATEDHEART),
+            _ => None,
+        }
+    }
+}
+
+const ATTACK_PRAYERS: &[AttackPrayer] = &[
+    AttackPrayer::NONE,
+    AttackPrayer::CLARITY,
+    AttackPrayer::IMPROVED,
+    AttackPrayer::INCREDIBLE,
+    AttackPrayer::CHIVALRY,
+    AttackPrayer::PIETY,
+];
+
+const STRENGTH_PRAYERS: &[StrengthPrayer] = &[
+    StrengthPrayer::NONE,
+    StrengthPrayer::BURST,
+    StrengthPrayer::SUPERHUMAN,
+    StrengthPrayer::ULTIMATE,
+    StrengthPrayer::CHIVALRY,
+    StrengthPrayer::PIETY,
+];
+
+const RANGED_PRAYERS: &[RangedPrayer] = &[
+    RangedPrayer::NONE,
+    RangedPrayer::SHARPEYE,
+    RangedPrayer::HAWKEYE,
+    RangedPrayer::EAGLEEYE,
+    RangedPrayer::RIGOUR,
+];
+
+const MAGIC_PRAYERS: &[MagicPrayer] = &[
+    MagicPrayer::NONE,
+    MagicPrayer::MYSTICWILL,
+    MagicPrayer::MYSTICLORE,
+    MagicPrayer::MYSTICMIGHT,
+    MagicPrayer::AUGURY,
+];
+
+/// Prayer and defence levels needed to use a prayer.
+fn unlocked(levels: &Levels, prayer: isize, defence: isize) -> bool {
+    levels.prayer.base >= prayer && levels.defence.base >= defence
+}
+
+impl AttackPrayer {
+    pub fn unlocked(&self, levels: &Levels) -> bool {
+        match self {
+            AttackPrayer::NONE => true,
+            AttackPrayer::CLARITY => unlocked(levels, 7, 1),
+            AttackPrayer::IMPROVED => unlocked(levels, 16, 1),
+            AttackPrayer::INCREDIBLE => unlocked(levels, 34, 1),
+            AttackPrayer::CHIVALRY => unlocked(levels, 60, 65),
+            AttackPrayer::PIETY => unlocked(levels, 70, 70),
+        }
+    }
+}
+
+impl StrengthPrayer {
+    pub fn unlocked(&self, levels: &Levels) -> bool {
+        match self {
+            StrengthPrayer::NONE => true,
+            StrengthPrayer::BURST => unlocked(levels, 4, 1),
+            StrengthPrayer::SUPERHUMAN => unlocked(levels, 13, 1),
+            StrengthPrayer::ULTIMATE => unlocked(levels, 31, 1),
+            StrengthPrayer::CHIVALRY => unlocked(levels, 60, 65),
+            StrengthPrayer::PIETY => unlocked(levels, 70, 70),
+        }
+    }
+}
+
+impl RangedPrayer {
+    pub fn unlocked(&self, levels: &Levels) -> bool {
+        match self {
+            RangedPrayer::NONE => true,
+            RangedPrayer::SHARPEYE => unlocked(levels, 8, 1),
+            RangedPrayer::HAWKEYE => unlocked(levels, 26, 1),
+            RangedPrayer::EAGLEEYE => unlocked(levels, 44, 1),
+            RangedPrayer::RIGOUR => unlocked(levels, 74, 70),
+        }
+    }
+}
+
+impl MagicPrayer {
+    pub fn unlocked(&self, levels: &Levels) -> bool {
+        match self {
+            MagicPrayer::NONE => true,
+            MagicPrayer::MYSTICWILL => unlocked(levels, 9, 1),
+            MagicPrayer::MYSTICLORE => unlocked(levels, 27, 1),
+            MagicPrayer::MYSTICMIGHT => unlocked(levels, 45, 1),
+            MagicPrayer::AUGURY => unlocked(levels, 77, 70),
+        }
+    }
+}
+
+/// Chivalry and Piety boost attack and strength together, so neither half can be combined
+/// with another attack or strength prayer.
+fn compatible(attack: &AttackPrayer, strength: &StrengthPrayer) -> bool {
+    matches!(
+        (attack, strength),
+        (AttackPrayer::CHIVALRY, StrengthPrayer::CHIVALRY)
+            | (AttackPrayer::PIETY, StrengthPrayer::PIETY)
+    ) || !(matches!(attack, AttackPrayer::CHIVALRY | AttackPrayer::PIETY)
+        || matches!(strength, StrengthPrayer::CHIVALRY | StrengthPrayer::PIETY))
+}
+
+/// Every combination of unlocked prayers and available potions that matters for
+/// `attack_type`. Boosts for the other combat styles are left off.
+pub fn candidates(
+    levels: &Levels,
+    potions: &[Potion],
+    attack_type: &AttackType,
+    context: &CombatContext,
+) -> Vec<Boosts> {
+    let potions: Vec<Potion> = potions
+        .iter()
+        .filter(|x| x.available(context))
+        .copied()
+        .collect();
+    let mut candidates = Vec::new();
+    match attack_type {
+        AttackType::STAB | AttackType::SLASH | AttackType::CRUSH => {
+            let mut attack_potions = vec![AttackPotion::NONE];
+            attack_potions.extend(potions.iter().filter_map(|x| x.attack()));
+            let mut strength_potions = vec![StrengthPotion::NONE];
+            strength_potions.extend(potions.iter().filter_map(|x| x.strength()));
+            for attack_prayer in ATTACK_PRAYERS.iter().filter(|x| x.unlocked(levels)) {
+                for strength_prayer in STRENGTH_PRAYERS.iter().filter(|x| x.unlocked(levels)) {
+                    if !compatible(attack_prayer, strength_prayer) {
+                        continue;
+                    }
+                    for attack_potion in &attack_potions {
+                        for strength_potion in &strength_potions {
+                            // The Dragon battleaxe drains attack unless a potion restores it
+                            let attack_potion = match (attack_potion, strength_potion) {
+                                (AttackPotion::NONE, StrengthPotion::DRAGONBATTLEAXE) => {
+                                    AttackPotion::DRAGONBATTLEAXE
+                                }
+                                _ => *attack_potion,
+                            };
+                            candidates.push(Boosts {
+                                attack_potion,
+                                attack_prayer: *attack_prayer,
+                                strength_potion: *strength_potion,
+                                strength_prayer: *strength_prayer,
+                                ..Default::default()
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        AttackType::RANGED => {
+            let mut ranged_potions = vec![RangedPotion::NONE];
+            ranged_potions.extend(potions.iter().filter_map(|x| x.ranged()));
+            for ranged_prayer in RANGED_PRAYERS.iter().filter(|x| x.unlocked(levels)) {
+                for ranged_potion in &ranged_potions {
+                    candidates.push(Boosts {
+                        ranged_potion: *ranged_potion,
+                        ranged_prayer: *ranged_prayer,
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+        AttackType::MAGIC | AttackType::SPELLCASTING | AttackType::DEFENSIVECASTING => {
+            let mut magic_potions = vec![MagicPotion::NONE];
+            magic_potions.extend(potions.iter().filter_map(|x| x.magic()));
+            for magic_prayer in MAGIC_PRAYERS.iter().filter(|x| x.unlocked(levels)) {
+                for magic_potion in &magic_potions {
+                    candidates.push(Boosts {
+                        magic_potion: *magic_potion,
+                        magic_prayer: *magic_prayer,
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+    }
+    let mut seen = HashSet::new();
+    candidates.retain(|x| seen.insert(*x));
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear_decay() {
+        let decay = BoostDecay::LINEAR;
+        assert_eq!(decay.remaining(19, 0), 19);
+        assert_eq!(decay.remaining(19, 5), 14);
+        assert_eq!(decay.remaining(19, 30), 0);
+        // Drains restore towards base at the same rate
+        assert_eq!(decay.remaining(-10, 4), -6);
+        assert_eq!(decay.remaining(-10, 20), 0);
+    }
+
+    #[test]
+    fn held_decay() {
+        let decay = BoostDecay::HELD(HELD_MINUTES);
+        assert_eq!(decay.remaining(19, 0), 19);
+        assert_eq!(decay.remaining(19, 4), 19);
+        assert_eq!(decay.remaining(19, 5), 0);
+        assert_eq!(StrengthPotion::OVERLOAD.decay(), decay);
+        assert_eq!(StrengthPotion::SUPERSTRENGTH.decay(), BoostDecay::LINEAR);
+    }
+
+    #[test]
+    fn redosing() {
+        let once = Trip {
+            minutes: 4,
+            redose_minutes: None,
+        };
+        assert_eq!(once.dose_ages().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+        let redosed = Trip {
+            minutes: 5,
+            redose_minutes: Some(2),
+        };
+        assert_eq!(redosed.dose_ages().collect::<Vec<_>>(), vec![0, 1, 0, 1, 0]);
+    }
+}
@@ -0,0 +1,49 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Raid {
+    #[default]
+    NONE,
+    CHAMBERS,
+    TOMBS,
+}
+
+/// Where and why the player is fighting, which decides whether slayer helmets, wilderness
+/// weapons, raid-only gear and raid-only boosts work.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(default)]
+pub struct CombatContext {
+    /// Whether the monster is the current slayer assignment.
+    pub on_task: bool,
+    pub wilderness: bool,
+    pub raid: Raid,
+    /// Raid level in the Tombs of Amascut, which monsters there scale with.
+    pub raid_level: isize,
+}
+
+impl Default for CombatContext {
+    fn default() -> Self {
+        CombatContext {
+            on_task: true,
+            wilderness: false,
+            raid: Raid::NONE,
+            raid_level: 0,
+        }
+    }
+}
+
+impl CombatContext {
+    /// Slayer tasks are never assigned inside a raid, so slayer helmets only work outside.
+    pub fn slayer_task(&self) -> bool {
+        self.on_task && self.raid == Raid::NONE
+    }
+
+    pub fn in_chambers(&self) -> bool {
+        self.raid == Raid::CHAMBERS
+    }
+
+    pub fn in_tombs(&self) -> bool {
+        self.raid == Raid::TOMBS
+    }
+}
@@ -0,0 +1,43 @@
+use crate::player::{AttackStyle, AttackType, Player};
+
+/// Anything that can be attacked, which is a monster or another player.
+pub trait Defender {
+    /// Defence roll against an attack of `attack_type`.
+    fn max_defence_roll(&self, attack_type: &AttackType) -> isize;
+
+    /// Whether the defender has an attribute, matched by prefix so that "vampyre" covers
+    /// every tier of vampyre. Players have none.
+    fn has_attribute(&self, attribute: &str) -> bool;
+
+    fn hitpoints(&self) -> isize;
+
+    /// Width in tiles, which decides how many times a Scythe of vitur hits.
+    fn size(&self) -> isize;
+
+    /// The higher of the magic level and magic attack bonus, which the Twisted bow scales with.
+    fn highest_magic(&self) -> isize;
+
+    /// Players take less damage from attacks their protection prayer covers.
+    fn damage_multiplier(&self, _attack_type: &AttackType) -> f64 {
+        1.0
+    }
+
+    /// Slayer helmets, the salve amulet and wilderness weapons only work on monsters.
+    fn is_player(&self) -> bool {
+        false
+    }
+
+    /// Expected damage the defender deals back per second to `attacker` fighting in
+    /// `style`. Only monster attacks are modelled, so other players deal none.
+    fn damage_per_second_to(&self, _attacker: &Player, _style: &(AttackStyle, AttackType)) -> f64 {
+        0.0
+    }
+
+    fn is_undead(&self) -> bool {
+        self.has_attribute("undead")
+    }
+
+    fn is_demon(&self) -> bool {
+        self.has_attribute("demon")
+    }
+}
@@ -0,0 +1,184 @@
+use std::fmt;
+
+/// Probability of dealing each amount of damage with a single attack, indexed by damage.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HitDistribution {
+    probabilities: Vec<f64>,
+}
+
+impl fmt::Display for HitDistribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "E[hit] = {:.3}, Var[hit] = {:.3}, max = {}",
+            self.expected(),
+            self.variance(),
+            self.max_hit()
+        )
+    }
+}
+
+impl HitDistribution {
+    pub fn miss() -> Self {
+        HitDistribution {
+            probabilities: vec![1.0],
+        }
+    }
+
+    /// A single roll that lands with probability `accuracy` and then deals anywhere
+    /// between zero and `max_hit` with equal probability.
+    pub fn linear(accuracy: f64, max_hit: isize) -> Self {
+        if max_hit <= 0 {
+            return HitDistribution::miss();
+        }
+        let per_value = accuracy / (max_hit + 1) as f64;
+        let mut probabilities = vec![per_value; max_hit as usize + 1];
+        probabilities[0] += 1.0 - accuracy;
+        HitDistribution { probabilities }
+    }
+
+    /// Builds a distribution from `(damage, probability)` pairs, summing repeated damage values.
+    pub fn from_outcomes(outcomes: impl IntoIterator<Item = (isize, f64)>) -> Self {
+        let mut probabilities = vec![0.0];
+        for (damage, p) in outcomes {
+            let damage = damage.max(0) as usize;
+            if damage >= probabilities.len() {
+                probabilities.resize(damage + 1, 0.0);
+            }
+            probabilities[damage] += p;
+        }
+        HitDistribution { probabilities }
+    }
+
+    pub fn max_hit(&self) -> isize {
+        self.probabilities.len() as isize - 1
+    }
+
+    pub fn probability(&self, damage: isize) -> f64 {
+        if damage < 0 {
+            return 0.0;
+        }
+        self.probabilities
+            .get(damage as usize)
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (isize, f64)> + '_ {
+        self.probabilities
+            .iter()
+            .enumerate()
+            .map(|(damage, p)| (damage as isize, *p))
+    }
+
+    pub fn expected(&self) -> f64 {
+        self.iter().map(|(damage, p)| damage as f64 * p).sum()
+    }
+
+    pub fn variance(&self) -> f64 {
+        let mean = self.expected();
+        self.iter()
+            .map(|(damage, p)| (damage as f64 - mean).powi(2) * p)
+            .sum()
+    }
+
+    /// Total damage of two independent hits, as for weapons that hit several times per attack.
+    pub fn combine(&self, other: &HitDistribution) -> Self {
+        let mut probabilities = vec![0.0; self.probabilities.len() + other.probabilities.len() - 1];
+        for (a, p) in self.iter() {
+            for (b, q) in other.iter() {
+                probabilities[(a + b) as usize] += p * q;
+            }
+        }
+        HitDistribution { probabilities }
+    }
+
+    /// Maps every damage value through `f`, merging outcomes that end up on the same value.
+    pub fn transform(&self, f: impl Fn(isize) -> isize) -> Self {
+        HitDistribution::from_outcomes(self.iter().map(|(damage, p)| (f(damage), p)))
+    }
+
+    /// Multiplies every hit by `multiplier`, rounding down like the game does.
+    pub fn scale(&self, multiplier: f64) -> Self {
+        self.transform(|damage| (damage as f64 * multiplier).floor() as isize)
+    }
+
+    pub fn cap(&self, max: isize) -> Self {
+        self.transform(|damage| damage.min(max))
+    }
+
+    /// Takes `other` with probability `chance` and `self` otherwise, as for random procs.
+    pub fn mix(&self, other: &HitDistribution, chance: f64) -> Self {
+        let len = self.probabilities.len().max(other.probabilities.len());
+        let probabilities = (0..len as isize)
+            .map(|damage| {
+                self.probability(damage) * (1.0 - chance) + other.probability(damage) * chance
+            })
+            .collect();
+        HitDistribution { probabilities }
+    }
+
+    /// Expected number of attacks to kill from every remaining hitpoint total up to
+    /// `hitpoints`, indexed by the remaining hitpoints. The remaining hitpoints are treated as
+    /// a Markov chain, so hits that would deal more than what is left are not over-counted.
+    pub fn expected_attacks_to_kill(&self, hitpoints: isize) -> Vec<f64> {
+        let hitpoints = hitpoints.max(0) as usize;
+        let miss = self.probability(0);
+        let mut expected = vec![0.0; hitpoints + 1];
+        if miss >= 1.0 {
+            expected[1..].iter_mut().for_each(|x| *x = f64::INFINITY);
+            return expected;
+        }
+        for hp in 1..=hitpoints {
+            let remaining: f64 = self
+                .iter()
+                .skip(1)
+                .map(|(damage, p)| p * expected[hp.saturating_sub(damage as usize)])
+                .sum();
+            expected[hp] = (1.0 + remaining) / (1.0 - miss);
+        }
+        expected
+    }
+
+    /// Expected seconds to kill a target with `hitpoints` when attacking every `interval` seconds.
+    pub fn expected_time_to_kill(&self, hitpoints: isize, interval: f64) -> f64 {
+        let attacks = self.expected_attacks_to_kill(hitpoints);
+        attacks[attacks.len() - 1] * interval
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guaranteed_hits() {
+        let hit = HitDistribution::from_outcomes([(1, 1.0)]);
+        assert_eq!(hit.expected_attacks_to_kill(3), vec![0.0, 1.0, 2.0, 3.0]);
+        assert_eq!(hit.expected_time_to_kill(3, 2.4), 3.0 * 2.4);
+    }
+
+    #[test]
+    fn overkill_is_not_counted() {
+        let hit = HitDistribution::from_outcomes([(5, 1.0)]);
+        assert_eq!(hit.expected_attacks_to_kill(3)[3], 1.0);
+    }
+
+    #[test]
+    fn half_hit_chance() {
+        // Every hit takes two attacks on average
+        let hit = HitDistribution::from_outcomes([(0, 0.5), (1, 0.5)]);
+        assert_eq!(hit.expected_attacks_to_kill(2), vec![0.0, 2.0, 4.0]);
+
+        // Two damage leaves 1 hitpoint after the first hit, so it takes two hits either way
+        let hit = HitDistribution::from_outcomes([(0, 0.5), (2, 0.5)]);
+        assert_eq!(hit.expected_attacks_to_kill(3)[3], 4.0);
+    }
+
+    #[test]
+    fn never_hits() {
+        let attacks = HitDistribution::miss().expected_attacks_to_kill(2);
+        assert_eq!(attacks[0], 0.0);
+        assert_eq!(attacks[2], f64::INFINITY);
+    }
+}
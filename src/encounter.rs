@@ -0,0 +1,216 @@
+use serde::Deserialize;
+
+use crate::player::{CombatClass, Monster, Player};
+use crate::simulation::{self, GearSet, Ranking, RunConfig, StyleResult};
+use crate::store::Store;
+
+fn full_share() -> f64 {
+    1.0
+}
+
+/// One phase of a fight, using the stats of a monster from the store.
+#[derive(Deserialize, Debug, Clone)]
+pub struct PhaseDefinition {
+    pub monster: String,
+    /// Share of the monster's hitpoints dealt in this phase, such as a third of the
+    /// Nightmare's hitpoints per shield.
+    #[serde(default = "full_share")]
+    pub hp_share: f64,
+    /// Combat classes the phase can be attacked with, or every class when not given.
+    pub styles: Option<Vec<CombatClass>>,
+}
+
+/// A boss that changes stats or weaknesses mid-fight, fought one phase after the other.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Encounter {
+    pub name: String,
+    pub phases: Vec<PhaseDefinition>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Phase {
+    pub definition: PhaseDefinition,
+    /// The phase's monster with only its share of hitpoints.
+    pub monster: Monster,
+}
+
+impl Encounter {
+    /// Looks up the monster of every phase, where `prepare` applies any raid scaling before
+    /// the hitpoints are split into phases.
+    pub fn phases(
+        &self,
+        api: &impl Store,
+        prepare: impl Fn(Monster) -> Monster,
+    ) -> Result<Vec<Phase>, String> {
+        self.phases
+            .iter()
+            .map(|x| {
+                let mut monster = prepare(api.get_monster(&x.monster).ok_or(x.monster.clone())?);
+                monster.hitpoints =
+                    ((monster.hitpoints as f64 * x.hp_share).round() as isize).max(1);
+                Ok(Phase {
+                    definition: x.clone(),
+                    monster,
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PhaseResult {
+    pub monster: String,
+    pub hitpoints: isize,
+    pub result: StyleResult,
+    pub gear: GearSet,
+}
+
+#[derive(Debug, Clone)]
+pub struct EncounterResult {
+    pub phases: Vec<PhaseResult>,
+    /// Time to kill the whole encounter, switching to the best loadout for every phase.
+    pub ttk: f64,
+}
+
+/// Fastest-killing loadout for every phase, or none when a phase can't be attacked with any
+/// gear set. Phases are always ranked by time to kill, whatever `config` ranks by, so that the
+/// summed time to kill is the fastest the encounter can go.
+pub fn run(player: &Player, phases: &[Phase], config: &RunConfig) -> Option<EncounterResult> {
+    let phases: Vec<PhaseResult> = phases
+        .iter()
+        .map(|x| {
+            let config = RunConfig {
+                ranking: Ranking::TTK,
+                classes: x.definition.styles.clone(),
+                ..config.clone()
+            };
+            let (result, gear) = simulation::run(player.clone(), &x.monster, &config)?;
+            Some(PhaseResult {
+                monster: x.definition.monster.clone(),
+                hitpoints: x.monster.hitpoints,
+                result,
+                gear,
+            })
+        })
+        .collect::<Option<_>>()?;
+    let ttk = phases.iter().map(|x| x.result.ttk).sum();
+    Some(EncounterResult { phases, ttk })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{monster, player};
+    use crate::player::{Equipment, Weapon};
+    use serde_json::json;
+
+    #[derive(Debug)]
+    struct StubStore {
+        monster: Monster,
+    }
+
+    impl Store for StubStore {
+        fn connect(_path: &str) -> Self {
+            unreachable!("the stub store is built directly")
+        }
+
+        fn get_weapon(&self, _name: &str) -> Option<Weapon> {
+            None
+        }
+
+        fn get_item(&self, _name: &str) -> Option<Equipment> {
+            None
+        }
+
+        fn get_monster(&self, name: &str) -> Option<Monster> {
+            Some(self.monster.clone()).filter(|x| x.name == name)
+        }
+    }
+
+    fn store() -> StubStore {
+        StubStore {
+            monster: monster(json!({"name": "Boss", "hitpoints": 75})),
+        }
+    }
+
+    fn phase(hp_share: f64, styles: Option<Vec<CombatClass>>) -> PhaseDefinition {
+        PhaseDefinition {
+            monster: String::from("Boss"),
+            hp_share,
+            styles,
+        }
+    }
+
+    fn encounter(phases: Vec<PhaseDefinition>) -> Encounter {
+        Encounter {
+            name: String::from("Test"),
+            phases,
+        }
+    }
+
+    #[test]
+    fn phase_hitpoints() {
+        let phases = encounter(vec![
+            phase(0.5, None),
+            phase(1.0 / 3.0, None),
+            phase(0.001, None),
+        ])
+        .phases(&store(), |x| x)
+        .unwrap();
+        let hitpoints: Vec<isize> = phases.iter().map(|x| x.monster.hitpoints).collect();
+        assert_eq!(hitpoints, vec![38, 25, 1]);
+    }
+
+    #[test]
+    fn unknown_phase_monster() {
+        let mut definition = phase(1.0, None);
+        definition.monster = String::from("Other");
+        let phases = encounter(vec![definition]).phases(&store(), |x| x);
+        assert_eq!(phases.err(), Some(String::from("Other")));
+    }
+
+    #[test]
+    fn phase_styles() {
+        let config = RunConfig::default();
+        let ranged = encounter(vec![
+            phase(0.5, None),
+            phase(0.5, Some(vec![CombatClass::RANGED])),
+        ])
+        .phases(&store(), |x| x)
+        .unwrap();
+        assert!(run(&player(), &ranged, &config).is_none());
+
+        let melee = encounter(vec![
+            phase(0.5, None),
+            phase(0.5, Some(vec![CombatClass::MELEE])),
+        ])
+        .phases(&store(), |x| x)
+        .unwrap();
+        let result = run(&player(), &melee, &config).unwrap();
+        let ttk: f64 = melee
+            .iter()
+            .zip(&result.phases)
+            .map(|(phase, x)| player().ttk(&phase.monster, &x.result.style))
+            .sum();
+        assert_eq!(result.phases.len(), 2);
+        assert!((result.ttk - ttk).abs() < 1e-9);
+    }
+
+    #[test]
+    fn phases_ranked_by_ttk() {
+        let phases = encounter(vec![phase(1.0, None)])
+            .phases(&store(), |x| x)
+            .unwrap();
+        let config = RunConfig {
+            ranking: Ranking::DPS,
+            ..Default::default()
+        };
+        let result = run(&player(), &phases, &config).unwrap();
+        let fastest = player()
+            .weapon_styles()
+            .iter()
+            .map(|x| player().ttk(&phases[0].monster, x))
+            .fold(f64::INFINITY, f64::min);
+        assert_eq!(result.phases[0].result.ttk, fastest);
+    }
+}
@@ -0,0 +1,333 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::distribution::HitDistribution;
+use crate::player::{AttackStyle, AttackType, Monster, Player};
+use crate::special::{SpecialAttack, SpecialEffect};
+
+/// Length of a game tick in seconds.
+pub const TICK: f64 = 0.6;
+const TICKS_PER_HOUR: u64 = 6000;
+/// Special attack energy regenerates 10% every 30 seconds.
+const SPEC_REGEN_TICKS: u64 = 50;
+/// Potion boosts wear off minute by minute.
+pub const TICKS_PER_MINUTE: u64 = 100;
+
+/// Xorshift generator, seeded through splitmix64 so that similar seeds still diverge.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        Rng {
+            state: (z ^ (z >> 31)).max(1),
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// Uniform sample in [0, 1).
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn sample(&mut self, distribution: &HitDistribution) -> isize {
+        let roll = self.next_f64();
+        let mut cumulative = 0.0;
+        for (damage, p) in distribution.iter() {
+            cumulative += p;
+            if roll < cumulative {
+                return damage;
+            }
+        }
+        distribution.max_hit()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EngineConfig {
+    pub seed: u64,
+    pub hours: u64,
+    pub respawn_ticks: u64,
+    /// Tiles between the player and the monster, which sets the projectile delay.
+    pub distance: isize,
+    /// Ticks between potion doses, or `None` to only drink once at the start.
+    pub redose_ticks: Option<u64>,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        EngineConfig {
+            seed: 0,
+            hours: 10,
+            respawn_ticks: 0,
+            distance: 1,
+            redose_ticks: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EngineReport {
+    /// Seconds from spawn until the killing blow lands, for every kill.
+    pub kill_times: Vec<f64>,
+    /// Kills completed in every full hour of the simulation.
+    pub kills_per_hour: Vec<usize>,
+    pub specs_used: usize,
+    pub spec_energy: isize,
+}
+
+impl EngineReport {
+    pub fn mean_kill_time(&self) -> f64 {
+        self.kill_times.iter().sum::<f64>() / self.kill_times.len().max(1) as f64
+    }
+
+    pub fn kill_time_percentile(&self, percentile: f64) -> f64 {
+        let mut times = self.kill_times.clone();
+        times.sort_unstable_by(|x, y| x.partial_cmp(y).unwrap());
+        let index = ((times.len() as f64 - 1.0) * percentile / 100.0).round() as usize;
+        times.get(index).copied().unwrap_or(f64::INFINITY)
+    }
+
+    pub fn mean_kills_per_hour(&self) -> f64 {
+        self.kills_per_hour.iter().sum::<usize>() as f64 / self.kills_per_hour.len().max(1) as f64
+    }
+}
+
+impl fmt::Display for EngineReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} kills ({} specs): mean {:.1}s, median {:.1}s, p90 {:.1}s, {:.1} kills/hour (min {}, max {})",
+            self.kill_times.len(),
+            self.specs_used,
+            self.mean_kill_time(),
+            self.kill_time_percentile(50.0),
+            self.kill_time_percentile(90.0),
+            self.mean_kills_per_hour(),
+            self.kills_per_hour.iter().min().unwrap_or(&0),
+            self.kills_per_hour.iter().max().unwrap_or(&0)
+        )
+    }
+}
+
+/// Ticks between an attack and its damage landing on the target.
+pub fn hit_delay(attack_type: &AttackType, distance: isize) -> u64 {
+    match attack_type {
+        AttackType::STAB | AttackType::SLASH | AttackType::CRUSH => 0,
+        AttackType::RANGED => (1 + (3 + distance) / 6) as u64,
+        AttackType::MAGIC | AttackType::SPELLCASTING | AttackType::DEFENSIVECASTING => {
+            (1 + (1 + distance) / 3) as u64
+        }
+    }
+}
+
+/// A special attack weapon the player switches to whenever there is enough energy.
+#[derive(Debug, Clone)]
+struct SpecWeapon {
+    player: Player,
+    style: (AttackStyle, AttackType),
+    special: SpecialAttack,
+    attack_ticks: u64,
+    delay: u64,
+    /// Special attack damage keyed by how far the monster's defence has been drained.
+    distributions: HashMap<isize, HitDistribution>,
+}
+
+/// Tick-by-tick replay of a player repeatedly killing a monster.
+#[derive(Debug)]
+pub struct Engine {
+    player: Player,
+    monster: Monster,
+    style: (AttackStyle, AttackType),
+    config: EngineConfig,
+    rng: Rng,
+    /// Main weapon damage keyed by minutes since the last dose and defence drained.
+    distributions: HashMap<(isize, isize), HitDistribution>,
+    spec: Option<SpecWeapon>,
+}
+
+impl Engine {
+    pub fn new(
+        player: &Player,
+        monster: &Monster,
+        style: (AttackStyle, AttackType),
+        config: EngineConfig,
+    ) -> Self {
+        Engine {
+            player: player.clone(),
+            monster: monster.clone(),
+            style,
+            rng: Rng::new(config.seed),
+            config,
+            distributions: HashMap::new(),
+            spec: None,
+        }
+    }
+
+    /// Uses the special attack of the weapon wielded by `player` whenever energy allows.
+    pub fn set_special(&mut self, player: &Player, style: (AttackStyle, AttackType)) {
+        self.spec = player
+            .gear
+            .weapon
+            .as_ref()
+            .and_then(|x| SpecialAttack::for_weapon(&x.name))
+            .map(|special| SpecWeapon {
+                player: player.clone(),
+                style,
+                special,
+                attack_ticks: (player.attack_interval(&style) / TICK).round() as u64,
+                delay: hit_delay(&style.1, self.config.distance),
+                distributions: HashMap::new(),
+            });
+    }
+
+    /// Rolls a main weapon attack `minutes` after the potions were drunk.
+    fn roll(&mut self, minutes: isize) -> isize {
+        let key = (minutes, self.monster.defence_drained());
+        if !self.distributions.contains_key(&key) {
+            self.player.set_boost_minutes(minutes);
+            let distribution = self.player.hit_distribution(&self.monster, &self.style);
+            self.distributions.insert(key, distribution);
+        }
+        self.rng.sample(&self.distributions[&key])
+    }
+
+    /// Rolls a special attack, whose effect is applied to the monster once the hit lands.
+    fn roll_special(&mut self) -> (isize, SpecialEffect) {
+        let spec = self
+            .spec
+            .as_mut()
+            .expect("rolling a special without a spec weapon");
+        let drained = self.monster.defence_drained();
+        let distribution = spec.distributions.entry(drained).or_insert_with(|| {
+            spec.player
+                .special_distribution(&self.monster, &spec.style)
+                .unwrap_or_else(HitDistribution::miss)
+        });
+        (self.rng.sample(distribution), spec.special.effect)
+    }
+
+    pub fn run(&mut self) -> EngineReport {
+        let end = self.config.hours * TICKS_PER_HOUR;
+        let attack_ticks = (self.player.attack_interval(&self.style) / TICK).round() as u64;
+        let delay = hit_delay(&self.style.1, self.config.distance);
+
+        let mut kill_times = Vec::new();
+        let mut kills_per_hour = vec![0; self.config.hours as usize];
+        let mut specs_used = 0;
+        let mut spec_energy = 100;
+        let mut last_dose = 0;
+        let mut cooldown = 0;
+        // (tick the hit lands, damage, effect on the monster)
+        let mut pending: Vec<(u64, isize, SpecialEffect)> = Vec::new();
+        let mut hitpoints = self.monster.hitpoints;
+        let mut spawned = None;
+        let mut respawn_at = 0;
+
+        for tick in 0..end {
+            if tick > 0 && tick % SPEC_REGEN_TICKS == 0 {
+                spec_energy = (spec_energy + 10).min(100);
+            }
+            if let Some(redose) = self.config.redose_ticks {
+                if tick - last_dose >= redose {
+                    last_dose = tick;
+                }
+            }
+
+            if spawned.is_none() && tick >= respawn_at {
+                hitpoints = self.monster.hitpoints;
+                self.monster.reset();
+                spawned = Some(tick);
+            }
+
+            if let Some(spawn) = spawned {
+                if cooldown == 0 {
+                    let spec = self
+                        .spec
+                        .as_ref()
+                        .filter(|x| x.special.energy <= spec_energy)
+                        .map(|x| (x.special.energy, x.attack_ticks, x.delay));
+                    if let Some((energy, spec_ticks, spec_delay)) = spec {
+                        let (damage, effect) = self.roll_special();
+                        pending.push((tick + spec_delay, damage, effect));
+                        cooldown = spec_ticks;
+                        spec_energy -= energy;
+                        specs_used += 1;
+                    } else {
+                        let minutes = ((tick - last_dose) / TICKS_PER_MINUTE) as isize;
+                        let damage = self.roll(minutes);
+                        pending.push((tick + delay, damage, SpecialEffect::NONE));
+                        cooldown = attack_ticks;
+                    }
+                }
+
+                for (_, damage, effect) in pending.iter().filter(|x| x.0 <= tick) {
+                    hitpoints -= damage;
+                    self.monster.drain_defence(effect, *damage);
+                }
+                pending.retain(|x| x.0 > tick);
+
+                if hitpoints <= 0 {
+                    kill_times.push((tick - spawn) as f64 * TICK);
+                    kills_per_hour[(tick / TICKS_PER_HOUR) as usize] += 1;
+                    pending.clear();
+                    spawned = None;
+                    respawn_at = tick + self.config.respawn_ticks.max(1);
+                }
+            }
+
+            cooldown = cooldown.saturating_sub(1);
+        }
+
+        EngineReport {
+            kill_times,
+            kills_per_hour,
+            specs_used,
+            spec_energy,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{monster, player};
+    use serde_json::json;
+
+    /// The player attacks without pause across kills, so the time spent on every kill,
+    /// respawns included, is a whole number of attacks just like the closed-form time to kill.
+    #[test]
+    fn matches_closed_form() {
+        let player = player();
+        let style = (AttackStyle::AGGRESSIVE, AttackType::CRUSH);
+        let config = EngineConfig {
+            seed: 7,
+            ..Default::default()
+        };
+
+        let small = monster(json!({"hitpoints": 50}));
+        let report = Engine::new(&player, &small, style, config.clone()).run();
+        let ttk = 3600.0 / report.mean_kills_per_hour();
+        let expected = player.ttk(&small, &style);
+        assert!((ttk - expected).abs() / expected < 0.02);
+
+        // Overkill and the pause after a respawn are negligible against a large monster, so
+        // its hitpoints over the kill time match the DPS.
+        let large = monster(json!({"hitpoints": 2000}));
+        let report = Engine::new(&player, &large, style, config).run();
+        let dps = large.hitpoints as f64 / report.mean_kill_time();
+        let expected = player.dps(&large, &style);
+        assert!((dps - expected).abs() / expected < 0.02);
+    }
+}
@@ -0,0 +1,63 @@
+use serde_json::{json, Value};
+
+use crate::levels::{Level, Levels};
+use crate::player::{
+    AttackPotion, AttackPrayer, Gear, MagicPotion, MagicPrayer, Monster, Player, RangedPotion,
+    RangedPrayer, StrengthPotion, StrengthPrayer,
+};
+
+/// 99 in every offensive skill and the defaults everywhere else.
+pub fn levels() -> Levels {
+    Levels {
+        attack: Level::new(99),
+        strength: Level::new(99),
+        ranged: Level::new(99),
+        magic: Level::new(99),
+        ..Default::default()
+    }
+}
+
+/// An unboosted player wearing `gear`.
+pub fn player_with(levels: Levels, gear: Gear) -> Player {
+    Player::new(
+        "Test",
+        levels,
+        AttackPotion::NONE,
+        AttackPrayer::NONE,
+        StrengthPotion::NONE,
+        StrengthPrayer::NONE,
+        RangedPotion::NONE,
+        RangedPrayer::NONE,
+        MagicPotion::NONE,
+        MagicPrayer::NONE,
+        gear,
+    )
+}
+
+/// An unboosted, unarmed player with 99 offensive skills.
+pub fn player() -> Player {
+    player_with(levels(), Gear::empty())
+}
+
+/// A monster with 100 hitpoints, 50 defence and no bonuses, where `stats` overrides any of
+/// the fields as they appear in the monster file.
+pub fn monster(stats: Value) -> Monster {
+    let mut monster = json!({
+        "name": "Test",
+        "hitpoints": 100,
+        "defence_level": 50,
+        "magic_level": 1,
+        "attack_magic": 0,
+        "size": 1,
+        "defence_stab": 0,
+        "defence_slash": 0,
+        "defence_crush": 0,
+        "defence_magic": 0,
+        "defence_ranged": 0,
+        "attributes": [],
+    });
+    if let (Some(base), Value::Object(stats)) = (monster.as_object_mut(), stats) {
+        base.extend(stats);
+    }
+    serde_json::from_value(monster).unwrap()
+}
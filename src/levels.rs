@@ -0,0 +1,121 @@
+use serde::Deserialize;
+use std::fmt;
+
+/// A skill's real level and its level right now, after drains, damage or boosts.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(from = "LevelInput")]
+pub struct Level {
+    pub base: isize,
+    pub current: isize,
+}
+
+/// Levels can be given as a plain number or with a separate current level.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LevelInput {
+    Base(isize),
+    Full { base: isize, current: Option<isize> },
+}
+
+impl From<LevelInput> for Level {
+    fn from(input: LevelInput) -> Self {
+        match input {
+            LevelInput::Base(base) => Level::new(base),
+            LevelInput::Full { base, current } => Level {
+                base,
+                current: current.unwrap_or(base),
+            },
+        }
+    }
+}
+
+impl Level {
+    pub fn new(base: isize) -> Self {
+        Level {
+            base,
+            current: base,
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.base == self.current {
+            write!(f, "{}", self.base)
+        } else {
+            write!(f, "{}/{}", self.current, self.base)
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(default)]
+pub struct Levels {
+    pub attack: Level,
+    pub strength: Level,
+    pub defence: Level,
+    pub ranged: Level,
+    pub magic: Level,
+    pub prayer: Level,
+    pub hitpoints: Level,
+}
+
+impl Default for Levels {
+    fn default() -> Self {
+        Levels {
+            attack: Level::new(1),
+            strength: Level::new(1),
+            defence: Level::new(1),
+            ranged: Level::new(1),
+            magic: Level::new(1),
+            prayer: Level::new(1),
+            hitpoints: Level::new(10),
+        }
+    }
+}
+
+impl Levels {
+    /// Combat level from base levels, taking the best of melee, ranged and magic.
+    pub fn combat_level(&self) -> isize {
+        let base = 0.25 * (self.defence.base + self.hitpoints.base + self.prayer.base / 2) as f64;
+        let melee = 0.325 * (self.attack.base + self.strength.base) as f64;
+        let ranged = 0.325 * (self.ranged.base * 3 / 2) as f64;
+        let magic = 0.325 * (self.magic.base * 3 / 2) as f64;
+        (base + melee.max(ranged).max(magic)).floor() as isize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn levels(combat: [isize; 7]) -> Levels {
+        let [attack, strength, defence, ranged, magic, prayer, hitpoints] = combat.map(Level::new);
+        Levels {
+            attack,
+            strength,
+            defence,
+            ranged,
+            magic,
+            prayer,
+            hitpoints,
+        }
+    }
+
+    #[test]
+    fn combat_levels() {
+        assert_eq!(Levels::default().combat_level(), 3);
+        assert_eq!(levels([99; 7]).combat_level(), 126);
+        // Ranged and magic count one and a half times their level against attack plus strength
+        assert_eq!(levels([60, 60, 70, 99, 1, 52, 80]).combat_level(), 92);
+        assert_eq!(levels([1, 1, 1, 1, 55, 1, 10]).combat_level(), 29);
+    }
+
+    #[test]
+    fn combat_level_ignores_boosts() {
+        let mut maxed = levels([99; 7]);
+        maxed.strength.current = 118;
+        maxed.hitpoints.current = 1;
+        assert_eq!(maxed.combat_level(), 126);
+    }
+}
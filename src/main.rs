@@ -1,6 +1,7 @@
+#![allow(clippy::upper_case_acronyms)]
+
 use serde::Deserialize;
-use tracing::{error, info, instrument, span, warn, Level};
-use tracing_subscriber;
+use tracing::{error, info, warn};
 
 use std::fs::File;
 use std::io::BufReader;
@@ -10,7 +11,59 @@ mod store;
 
 #[allow(dead_code)]
 mod player;
-use player::{AttackPotion, AttackPrayer, EquipmentSlot, Gear, StrengthPotion, StrengthPrayer};
+use player::{
+    AttackPotion, AttackPrayer, Gear, MagicPotion, MagicPrayer, RangedPotion, RangedPrayer,
+    StrengthPotion, StrengthPrayer,
+};
+
+#[allow(dead_code)]
+mod spell;
+
+#[allow(dead_code)]
+mod levels;
+
+#[allow(dead_code)]
+mod boosts;
+use boosts::{Potion, Trip};
+use levels::{Level, Levels};
+
+#[allow(dead_code)]
+mod context;
+use context::{CombatContext, Raid};
+
+#[allow(dead_code)]
+mod defender;
+
+#[allow(dead_code)]
+mod distribution;
+
+#[allow(dead_code)]
+mod encounter;
+use encounter::Encounter;
+
+#[allow(dead_code)]
+mod engine;
+
+#[cfg(test)]
+mod fixtures;
+
+#[allow(dead_code)]
+mod special;
+
+#[allow(dead_code)]
+mod sets;
+
+#[allow(dead_code)]
+mod passive;
+
+#[allow(dead_code)]
+mod prayer;
+use prayer::{Inventory, ProtectionPrayer};
+use spell::Spell;
+
+#[allow(dead_code)]
+mod scaling;
+use scaling::{ChambersParty, TombsRaid};
 
 #[allow(dead_code)]
 mod simulation;
@@ -18,35 +71,146 @@ mod simulation;
 #[derive(Deserialize, Debug, Clone)]
 struct ParsedFile {
     player_name: String,
+    /// Every combat skill, which takes over from the single level fields below when given.
+    levels: Option<Levels>,
+    #[serde(default = "default_level")]
     attack_level: isize,
+    #[serde(default = "default_level")]
     strength_level: isize,
+    #[serde(default = "default_level")]
+    ranged_level: isize,
+    #[serde(default = "default_level")]
+    magic_level: isize,
+    #[serde(default = "default_hitpoints")]
+    hitpoints_level: isize,
+    /// Hitpoints at the start of the fight, which Dharok's set cares about.
+    current_hitpoints: Option<isize>,
+    spell: Option<String>,
     monster_name: String,
     equipment: Vec<String>,
+    #[serde(default)]
+    ranking: simulation::Ranking,
+    #[serde(default)]
+    seed: u64,
+    /// Where and why the fight happens. The older `in_chambers` and `in_wilderness` flags
+    /// still apply, and `chambers` or `tombs` decide the raid.
+    context: Option<CombatContext>,
+    #[serde(default)]
+    in_chambers: bool,
+    #[serde(default)]
+    in_wilderness: bool,
+    /// Party to scale the monster for, which also puts the fight inside Chambers of Xeric.
+    chambers: Option<ChambersParty>,
+    /// Raid level, party and invocations to scale the monster for inside the Tombs of Amascut.
+    tombs: Option<TombsRaid>,
+    #[serde(default)]
+    attack_potion: AttackPotion,
+    #[serde(default)]
+    strength_potion: StrengthPotion,
+    #[serde(default)]
+    ranged_potion: RangedPotion,
+    #[serde(default)]
+    magic_potion: MagicPotion,
+    #[serde(default)]
+    attack_prayer: AttackPrayer,
+    #[serde(default)]
+    strength_prayer: StrengthPrayer,
+    #[serde(default)]
+    ranged_prayer: RangedPrayer,
+    #[serde(default)]
+    magic_prayer: MagicPrayer,
+    #[serde(default)]
+    protection_prayer: ProtectionPrayer,
+    /// Potions in the bank, which makes the optimizer pick prayers and potions as well.
+    potions: Option<Vec<Potion>>,
+    #[serde(default)]
+    trip: Trip,
+    /// Restores and other supplies brought along on the trip.
+    #[serde(default)]
+    inventory: Inventory,
+    /// Another loadout file with a player to fight, wearing everything it lists.
+    opponent: Option<String>,
+    /// A boss fought over several phases, each with the stats of a monster in the store.
+    encounter: Option<Encounter>,
 }
 
-fn load_player(
-    filename: &str,
-    api: &impl store::Store,
-) -> Option<(player::Player, player::Monster)> {
+fn default_level() -> isize {
+    1
+}
+
+fn default_hitpoints() -> isize {
+    10
+}
+
+impl ParsedFile {
+    fn levels(&self) -> Levels {
+        self.levels.unwrap_or_else(|| Levels {
+            attack: Level::new(self.attack_level),
+            strength: Level::new(self.strength_level),
+            ranged: Level::new(self.ranged_level),
+            magic: Level::new(self.magic_level),
+            hitpoints: Level {
+                base: self.hitpoints_level,
+                current: self.current_hitpoints.unwrap_or(self.hitpoints_level),
+            },
+            ..Default::default()
+        })
+    }
+
+    /// The raid and raid level always follow the scaling in the loadout, so that the monster
+    /// and the raid-only gear and boosts can't disagree about where the fight happens.
+    fn context(&self) -> CombatContext {
+        let context = self.context.unwrap_or_default();
+        let (raid, raid_level) = match (&self.tombs, &self.chambers) {
+            (Some(tombs), _) => (Raid::TOMBS, tombs.raid_level()),
+            (None, Some(_)) => (Raid::CHAMBERS, 0),
+            (None, None) if self.in_chambers => (Raid::CHAMBERS, 0),
+            (None, None) => (context.raid, context.raid_level),
+        };
+        CombatContext {
+            raid,
+            raid_level,
+            wilderness: context.wilderness || self.in_wilderness,
+            ..context
+        }
+    }
+}
+
+fn parse_loadout(filename: &str) -> Option<ParsedFile> {
     let file = File::open(filename).ok()?;
     let reader = BufReader::new(file);
-    let parsed_file: ParsedFile = serde_json::from_reader(reader).ok()?;
+    serde_json::from_reader(reader).ok()
+}
 
+fn build_player(parsed_file: &ParsedFile, api: &impl store::Store) -> player::Player {
     let mut player = player::Player::new(
         &parsed_file.player_name,
-        parsed_file.attack_level,
-        parsed_file.strength_level,
-        AttackPotion::NONE,
-        AttackPrayer::NONE,
-        StrengthPotion::NONE,
-        StrengthPrayer::NONE,
+        parsed_file.levels(),
+        parsed_file.attack_potion,
+        parsed_file.attack_prayer,
+        parsed_file.strength_potion,
+        parsed_file.strength_prayer,
+        parsed_file.ranged_potion,
+        parsed_file.ranged_prayer,
+        parsed_file.magic_potion,
+        parsed_file.magic_prayer,
         Gear::empty(),
-    );
+    )
+    .with_protection(parsed_file.protection_prayer);
+
+    player.context = parsed_file.context();
+
+    if let Some(name) = &parsed_file.spell {
+        player.spell = Spell::from_name(name);
+        if player.spell.is_none() {
+            warn!("Warning: spell {} was not matched :(", name);
+        }
+    }
 
     // Parse all equipment
     for eq in &parsed_file.equipment {
-        let weapon = api.get_weapon(&eq);
-        let item = api.get_item(&eq);
+        let weapon = api.get_weapon(eq);
+        let item = api.get_item(eq);
 
         // Add armour and weapons, but warn if equipment was not matched.
         match (&weapon, &item) {
@@ -56,10 +220,52 @@ fn load_player(
             (Some(_), Some(_)) => error!("This should not happen!"),
         }
     }
-    info!("Test end of stuff");
+    player
+}
+
+fn load_player(
+    filename: &str,
+    api: &impl store::Store,
+) -> Option<(player::Player, player::Monster, ParsedFile)> {
+    let parsed_file = parse_loadout(filename)?;
+    let player = build_player(&parsed_file, api);
+    info!("Combat level: {}", player.levels.combat_level());
+
+    let monster = scale_monster(api.get_monster(&parsed_file.monster_name)?, &parsed_file);
+    Some((player, monster, parsed_file))
+}
+
+/// Applies the raid scaling of the loadout to a monster from the store.
+fn scale_monster(monster: player::Monster, parsed_file: &ParsedFile) -> player::Monster {
+    let context = parsed_file.context();
+    match (context.raid, &parsed_file.tombs, &parsed_file.chambers) {
+        (Raid::TOMBS, Some(raid), _) => {
+            for name in raid.unknown_invocations() {
+                warn!("Warning: invocation {} was not matched :(", name);
+            }
+            let monster = scaling::scale_tombs(&monster, raid, &context);
+            info!(
+                "Scaled {} to {} hitpoints at raid level {}",
+                monster.name, monster.hitpoints, context.raid_level
+            );
+            monster
+        }
+        (Raid::CHAMBERS, _, Some(party)) => {
+            let monster = scaling::scale_chambers(&monster, party);
+            info!("Scaled {} to {} hitpoints", monster.name, monster.hitpoints);
+            monster
+        }
+        _ => monster,
+    }
+}
 
-    let monster = api.get_monster(&parsed_file.monster_name)?;
-    Some((player, monster.clone()))
+/// The opponent wears everything in its loadout and defends in its weapon's first style.
+fn load_opponent(filename: &str, api: &impl store::Store) -> Option<player::Player> {
+    let parsed_file = parse_loadout(filename)?;
+    let mut opponent = build_player(&parsed_file, api);
+    opponent.gear = opponent.equipment.wear_all();
+    let stance = opponent.weapon_styles().first().copied();
+    Some(stance.map_or(opponent.clone(), |x| opponent.with_stance(&x)))
 }
 
 /* What modules to have:
@@ -75,13 +281,109 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let api: store::FileStore = store::Store::connect("osrsbox-db");
 
     info!("Store loaded..");
-    if let Some((player, monster)) = load_player("./loadout.json", &api) {
+    if let Some((player, monster, parsed_file)) = load_player("./loadout.json", &api) {
+        let run_config = simulation::RunConfig {
+            ranking: parsed_file.ranking,
+            potions: parsed_file.potions.clone(),
+            trip: parsed_file.trip,
+            inventory: parsed_file.inventory.clone(),
+            classes: None,
+        };
         info!(
             "Attack styles: {:#?}",
-            simulation::run_attack_styles(&player, &monster)
+            simulation::run_attack_styles(&player, &monster, &run_config)
         );
-        let better = simulation::run(player, &monster);
+        let Some(better) = simulation::run(player.clone(), &monster, &run_config) else {
+            error!("No gear set can attack {} :(", monster.name);
+            return Ok(());
+        };
         info!("Better player: {:#?}", better);
+
+        let equipped = better
+            .1
+            .equip_player(&player)
+            .with_boosts(&better.0.boosts)
+            .with_protection(better.0.protection);
+        info!("Boosts: {}", better.0.boosts);
+        for contribution in simulation::boost_contributions(&equipped, &monster, &better.0.style) {
+            info!("  {}: +{:.3} dps", contribution.choice, contribution.dps);
+        }
+        let spec = simulation::run_special_attacks(&equipped, &monster, &better.0);
+        info!("Spec weapon: {:#?}", spec);
+        for plan in simulation::run_spec_plans(&equipped, &better.0.style, &monster) {
+            info!(
+                "{} x {}: {:.1}s ttk (without specs {:.1}s)",
+                plan.specs, plan.weapon, plan.ttk, plan.baseline_ttk
+            );
+        }
+
+        info!(
+            "Peak dps {:.3}, trip-average dps {:.3} over {} minutes",
+            better.0.dps, better.0.trip_dps, parsed_file.trip.minutes
+        );
+        info!(
+            "Prayer drains {:.2} points per minute and lasts {:.1} minutes",
+            equipped.prayer_drain_per_minute(),
+            better.0.prayer_minutes
+        );
+        info!(
+            "Expected damage taken: {:.3} per second with {}",
+            better.0.damage_taken, better.0.protection
+        );
+        info!(
+            "{:.1} kills over {:.1} minutes of fighting per trip",
+            better.0.kills_per_trip, better.0.fighting_minutes
+        );
+
+        let config = engine::EngineConfig {
+            seed: parsed_file.seed,
+            redose_ticks: parsed_file
+                .trip
+                .redose_minutes
+                .map(|x| x.max(1) as u64 * engine::TICKS_PER_MINUTE),
+            ..Default::default()
+        };
+        let mut engine = engine::Engine::new(&equipped, &monster, better.0.style, config);
+        if let Some(spec) = &spec {
+            engine.set_special(&equipped.with_weapon(&spec.weapon), spec.style);
+        }
+        info!("Simulated: {}", engine.run());
+
+        if let Some(filename) = &parsed_file.opponent {
+            match load_opponent(filename, &api) {
+                Some(opponent) => {
+                    let against = simulation::run(player.clone(), &opponent, &run_config);
+                    info!("Against {}: {:#?}", opponent.name, against);
+                }
+                None => warn!("Unable to parse opponent loadout {} :(", filename),
+            }
+        }
+
+        if let Some(encounter) = &parsed_file.encounter {
+            match encounter.phases(&api, |x| scale_monster(x, &parsed_file)) {
+                Ok(phases) => match encounter::run(&player, &phases, &run_config) {
+                    Some(result) => {
+                        for phase in &result.phases {
+                            info!(
+                                "{} ({} hitpoints): {:.1}s ttk with [{} - {}] {}",
+                                phase.monster,
+                                phase.hitpoints,
+                                phase.result.ttk,
+                                phase.result.style.0,
+                                phase.result.style.1,
+                                phase.gear
+                            );
+                        }
+                        info!(
+                            "{}: {:.1}s ttk over every phase",
+                            encounter.name, result.ttk
+                        );
+                    }
+                    None => warn!("Some phase of {} can't be attacked :(", encounter.name),
+                },
+                Err(name) => warn!("Warning: phase monster {} was not matched :(", name),
+            }
+        }
     } else {
         error!("Unable to parse loadout :(");
     }
@@ -0,0 +1,157 @@
+use crate::defender::Defender;
+use crate::player::AttackType;
+
+/// Accuracy and damage boost a weapon or spell gets against monsters with an attribute.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeaponPassive {
+    pub source: &'static str,
+    pub attribute: &'static str,
+    pub accuracy: f64,
+    pub damage: f64,
+}
+
+const fn passive(
+    source: &'static str,
+    attribute: &'static str,
+    accuracy: f64,
+    damage: f64,
+) -> WeaponPassive {
+    WeaponPassive {
+        source,
+        attribute,
+        accuracy,
+        damage,
+    }
+}
+
+// Sources are weapon names, or spell names while casting, and are matched by prefix.
+// Attributes are matched by prefix too, so "vampyre" covers every tier of vampyre.
+#[rustfmt::skip]
+const PASSIVES: &[WeaponPassive] = &[
+    passive("Dragon hunter lance", "dragon", 1.2, 1.2),
+    passive("Dragon hunter crossbow", "dragon", 1.3, 1.25),
+    passive("Arclight", "demon", 1.7, 1.7),
+    passive("Darklight", "demon", 1.6, 1.6),
+    passive("Silverlight", "demon", 1.6, 1.6),
+    passive("Inferior Demonbane", "demon", 1.2, 1.0),
+    passive("Superior Demonbane", "demon", 1.2, 1.0),
+    passive("Dark Demonbane", "demon", 1.2, 1.0),
+    passive("Keris", "kalphite", 1.0, 4.0 / 3.0),
+    passive("Leaf-bladed battleaxe", "leafy", 1.175, 1.175),
+    passive("Ivandis flail", "vampyre", 1.0, 1.2),
+    passive("Blisterwood flail", "vampyre", 1.05, 1.25),
+    passive("Blisterwood sickle", "vampyre", 1.05, 1.15),
+];
+
+/// Chance for a Keris to deal triple damage against kalphites.
+pub const KERIS_TRIPLE_CHANCE: f64 = 1.0 / 51.0;
+
+/// Passives of `source` that trigger against the target.
+pub fn passives<'a, D: Defender>(
+    source: &'a str,
+    target: &'a D,
+) -> impl Iterator<Item = &'static WeaponPassive> + 'a {
+    PASSIVES
+        .iter()
+        .filter(move |x| source.starts_with(x.source) && target.has_attribute(x.attribute))
+}
+
+/// Weapons powered by revenant ether that only get their bonus in the wilderness.
+const WILDERNESS_WEAPONS: &[&str] = &[
+    "Viggora's chainmace",
+    "Ursine chainmace",
+    "Craw's bow",
+    "Webweaver bow",
+    "Thammaron's sceptre",
+    "Accursed sceptre",
+];
+
+/// Accuracy and damage multiplier of wilderness weapons, which only hit harder against
+/// monsters in the wilderness and never against players.
+pub fn wilderness_bonus(source: &str, target: &impl Defender, in_wilderness: bool) -> f64 {
+    if in_wilderness
+        && !target.is_player()
+        && WILDERNESS_WEAPONS.iter().any(|x| source.starts_with(x))
+    {
+        1.5
+    } else {
+        1.0
+    }
+}
+
+/// Whether an attack can hurt the monster at all, as some monsters are immune to anything
+/// but specific weapons and some spells can only be cast on specific monsters.
+pub fn can_damage(
+    target: &impl Defender,
+    attack_type: &AttackType,
+    source: &str,
+    ammo: &str,
+) -> bool {
+    let silver = [
+        "Rod of ivandis",
+        "Ivandis flail",
+        "Blisterwood",
+        "Silver sickle (b)",
+    ];
+    let demonbane = ["Inferior Demonbane", "Superior Demonbane", "Dark Demonbane"];
+    if demonbane.contains(&source) && !target.has_attribute("demon") {
+        return false;
+    }
+    if target.has_attribute("leafy") {
+        // Kurask and Turoth only take damage from leaf-bladed weapons, broad ammo and Magic Dart
+        return source.starts_with("Leaf-bladed")
+            || source == "Magic Dart"
+            || (*attack_type == AttackType::RANGED && ammo.to_lowercase().contains("broad"));
+    }
+    if target.has_attribute("vampyre3") {
+        return source.starts_with("Ivandis flail") || source.starts_with("Blisterwood");
+    }
+    if target.has_attribute("vampyre2") {
+        return silver.iter().any(|x| source.starts_with(x));
+    }
+    true
+}
+
+/// Highest target magic the Twisted bow scales with, raised inside Chambers of Xeric.
+const TWISTED_BOW_CAP: isize = 250;
+const TWISTED_BOW_CHAMBERS_CAP: isize = 350;
+
+/// Twisted bow accuracy and damage multipliers against a target whose highest magic
+/// value, its magic level or magic attack bonus, is `magic`.
+pub fn twisted_bow_bonus(magic: isize, in_chambers: bool) -> (f64, f64) {
+    let cap = if in_chambers {
+        TWISTED_BOW_CHAMBERS_CAP
+    } else {
+        TWISTED_BOW_CAP
+    };
+    let magic = magic.clamp(0, cap);
+    let scaled = 3 * magic / 10;
+    let accuracy = (140 + (3 * magic - 10).div_euclid(100) - (scaled - 100).pow(2) / 100).min(140);
+    let damage = (250 + (3 * magic - 14).div_euclid(100) - (scaled - 140).pow(2) / 100).min(250);
+    (accuracy as f64 / 100.0, damage as f64 / 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn twisted_bow_low_magic() {
+        // The first term floors to -1 rather than truncating to 0
+        assert_eq!(twisted_bow_bonus(0, false), (0.39, 0.53));
+        assert_eq!(twisted_bow_bonus(3, false), (0.39, 0.53));
+    }
+
+    #[test]
+    fn twisted_bow_cap() {
+        assert_eq!(twisted_bow_bonus(250, false), (1.4, 2.15));
+        assert_eq!(twisted_bow_bonus(400, false), (1.4, 2.15));
+    }
+
+    #[test]
+    fn twisted_bow_chambers_cap() {
+        assert_eq!(twisted_bow_bonus(350, true), (1.4, 2.48));
+        assert_eq!(twisted_bow_bonus(400, true), (1.4, 2.48));
+        assert_eq!(twisted_bow_bonus(250, true), twisted_bow_bonus(250, false));
+    }
+}
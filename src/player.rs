@@ -1,28 +1,88 @@
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer};
 use std::cmp::{Eq, PartialEq};
 use std::collections::HashMap;
 use std::fmt;
 use std::hash::Hash;
 
+use crate::boosts::{BoostDecay, Boosts, Trip};
+use crate::context::CombatContext;
+use crate::defender::Defender;
+use crate::distribution::HitDistribution;
+use crate::levels::Levels;
+use crate::passive::{self, KERIS_TRIPLE_CHANCE};
+use crate::prayer::{self, Inventory, ProtectionPrayer};
+use crate::sets::{self, ActiveSet, SetEffect};
+use crate::special::{SpecialAttack, SpecialEffect};
+use crate::spell::{Spell, Spellbook};
+
 #[allow(dead_code)]
-#[derive(Debug, Clone)]
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
 pub enum StrengthPotion {
+    #[default]
     NONE,
     STRENGTH,
     SUPERSTRENGTH,
+    DIVINESUPERSTRENGTH,
+    COMBAT,
+    /// Chambers of Xeric overload (+).
+    OVERLOAD,
+    /// Tombs of Amascut smelling salts.
+    SMELLINGSALTS,
+    ZAMORAKBREW,
+    /// Dragon battleaxe special attack, which trades other levels for strength.
+    DRAGONBATTLEAXE,
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Clone)]
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
 pub enum AttackPotion {
+    #[default]
     NONE,
     ATTACK,
     SUPERATTACK,
+    DIVINESUPERATTACK,
+    COMBAT,
+    OVERLOAD,
+    SMELLINGSALTS,
+    ZAMORAKBREW,
+    DRAGONBATTLEAXE,
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Clone)]
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum RangedPotion {
+    #[default]
+    NONE,
+    RANGING,
+    SUPERRANGING,
+    DIVINERANGING,
+    OVERLOAD,
+    SMELLINGSALTS,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum MagicPotion {
+    #[default]
+    NONE,
+    MAGIC,
+    SUPERMAGIC,
+    DIVINEMAGIC,
+    OVERLOAD,
+    SMELLINGSALTS,
+    IMBUEDHEART,
+    SATURATEDHEART,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
 pub enum AttackPrayer {
+    #[default]
     NONE,
     CLARITY,
     IMPROVED,
@@ -32,8 +92,10 @@ pub enum AttackPrayer {
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Clone)]
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
 pub enum StrengthPrayer {
+    #[default]
     NONE,
     BURST,
     SUPERHUMAN,
@@ -42,6 +104,30 @@ pub enum StrengthPrayer {
     PIETY,
 }
 
+#[allow(dead_code)]
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum RangedPrayer {
+    #[default]
+    NONE,
+    SHARPEYE,
+    HAWKEYE,
+    EAGLEEYE,
+    RIGOUR,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum MagicPrayer {
+    #[default]
+    NONE,
+    MYSTICWILL,
+    MYSTICLORE,
+    MYSTICMIGHT,
+    AUGURY,
+}
+
 #[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum AttackStyle {
@@ -49,6 +135,8 @@ pub enum AttackStyle {
     CONTROLLED,
     ACCURATE,
     DEFENSIVE,
+    RAPID,
+    LONGRANGE,
     RANGED,
     MAGIC,
 }
@@ -62,6 +150,8 @@ impl fmt::Display for AttackStyle {
                 AttackStyle::MAGIC => "magic",
                 AttackStyle::RANGED => "ranged",
                 AttackStyle::DEFENSIVE => "defensive",
+                AttackStyle::RAPID => "rapid",
+                AttackStyle::LONGRANGE => "longrange",
                 AttackStyle::ACCURATE => "accurate",
                 AttackStyle::CONTROLLED => "controlled",
                 AttackStyle::AGGRESSIVE => "agressive",
@@ -119,6 +209,21 @@ impl SpareGear {
             self.equipment.push(equipment.clone());
         }
     }
+    /// Gear wearing every spare item in its slot, where later items replace earlier ones
+    /// and the first weapon is wielded.
+    pub fn wear_all(&self) -> Gear {
+        let mut gear = Gear::empty();
+        for item in &self.equipment {
+            gear.add_equipment(&item.equipment.slot, Some(item.clone()));
+        }
+        if let Some(weapon) = self.spare_weapons.first() {
+            if weapon.equipment.slot == EquipmentSlot::TWOHAND {
+                gear.add_equipment(&EquipmentSlot::SHIELD, None);
+            }
+            gear.add_weapon(Some(weapon.clone()));
+        }
+        gear
+    }
 }
 
 #[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -229,6 +334,22 @@ impl _Equipment {
             }
         }
     }
+
+    pub fn defence_bonus(&self, style: &AttackType) -> isize {
+        match style {
+            AttackType::STAB => self.defence_stab,
+            AttackType::SLASH => self.defence_slash,
+            AttackType::CRUSH => self.defence_crush,
+            AttackType::RANGED => self.defence_ranged,
+            AttackType::MAGIC | AttackType::SPELLCASTING | AttackType::DEFENSIVECASTING => {
+                self.defence_magic
+            }
+        }
+    }
+
+    pub fn prayer_bonus(&self) -> isize {
+        self.prayer
+    }
 }
 
 #[derive(Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
@@ -272,76 +393,221 @@ impl Gear {
         self.weapon = weapon.clone();
     }
 
-    pub fn void_bonus(&self) -> f64 {
-        let head = self.equipment.get(&EquipmentSlot::HEAD);
-        let body = self.equipment.get(&EquipmentSlot::BODY);
-        let legs = self.equipment.get(&EquipmentSlot::LEGS);
-        let hands = self.equipment.get(&EquipmentSlot::HANDS);
-        match (head, body, legs, hands) {
-            (Some(Some(head)), Some(Some(body)), Some(Some(legs)), Some(Some(hands)))
-                if (head.name == "Void melee helm"
-                    || head.name == "Void ranger helm"
-                    || head.name == "Void mage helm")
-                    && (body.name == "Void knight top" || body.name == "Elite void top")
-                    && (legs.name == "Void knight robe" || legs.name == "Elite void robe")
-                    && hands.name == "Void knight gloves" =>
-            {
-                1.1
+    /// Name of the item worn in `slot`, where both weapon slots refer to the wielded weapon.
+    fn worn(&self, slot: &EquipmentSlot) -> Option<&str> {
+        match slot {
+            EquipmentSlot::WEAPON | EquipmentSlot::TWOHAND => {
+                self.weapon.as_ref().map(|x| x.name.as_str())
             }
-            _ => 1.0,
+            _ => self
+                .equipment
+                .get(slot)
+                .and_then(|x| x.as_ref())
+                .map(|x| x.name.as_str()),
         }
     }
 
-    pub fn regular_bonus(&self, on_task: bool) -> f64 {
-        match self.equipment.get(&EquipmentSlot::HEAD) {
-            Some(Some(head))
-                if (head.name == "Slayer helmet" || head.name == "Slayer helmet (i)")
-                    && on_task =>
-            {
+    pub fn set_effects(&self) -> Vec<ActiveSet> {
+        sets::active_sets(|slot| self.worn(slot))
+    }
+
+    pub fn has_set(&self, effect: SetEffect) -> bool {
+        self.set_effects().iter().any(|x| x.effect == effect)
+    }
+
+    /// Melee void boosts both melee accuracy and strength by 10%.
+    pub fn melee_void_bonus(&self) -> f64 {
+        if self.has_set(SetEffect::VOIDMELEE) {
+            1.1
+        } else {
+            1.0
+        }
+    }
+
+    pub fn ranged_void_accuracy_bonus(&self) -> f64 {
+        if self.has_set(SetEffect::VOIDRANGED) {
+            1.1
+        } else {
+            1.0
+        }
+    }
+
+    /// Elite void raises the ranged strength boost from 10% to 12.5%.
+    pub fn ranged_void_strength_bonus(&self) -> f64 {
+        if self.has_set(SetEffect::ELITEVOIDRANGED) {
+            1.125
+        } else if self.has_set(SetEffect::VOIDRANGED) {
+            1.1
+        } else {
+            1.0
+        }
+    }
+
+    pub fn magic_void_accuracy_bonus(&self) -> f64 {
+        if self.has_set(SetEffect::VOIDMAGIC) {
+            1.45
+        } else {
+            1.0
+        }
+    }
+
+    /// Percentage of magic damage added by elite magic void.
+    pub fn magic_void_damage_bonus(&self) -> f64 {
+        if self.has_set(SetEffect::ELITEVOIDMAGIC) {
+            2.5
+        } else {
+            0.0
+        }
+    }
+
+    /// Multiplier on the attack roll from every set effect worn.
+    pub fn set_accuracy_bonus(&self, attack_type: &AttackType) -> f64 {
+        self.set_effects()
+            .iter()
+            .map(|x| x.accuracy_bonus(attack_type))
+            .product()
+    }
+
+    /// Multiplier on the max hit from every set effect worn.
+    pub fn set_damage_bonus(&self, attack_type: &AttackType) -> f64 {
+        self.set_effects()
+            .iter()
+            .map(|x| x.damage_bonus(attack_type))
+            .product()
+    }
+
+    pub fn ammo(&self) -> Option<&Equipment> {
+        self.equipment
+            .get(&EquipmentSlot::AMMO)
+            .and_then(|x| x.as_ref())
+    }
+
+    /// Whether the ammo slot holds what the wielded weapon fires.
+    pub fn has_required_ammo(&self) -> bool {
+        let required = match self.weapon.as_ref().and_then(|x| x.required_ammo()) {
+            Some(required) => required,
+            None => return true,
+        };
+        self.ammo()
+            .is_some_and(|x| x.name.to_lowercase().contains(required))
+    }
+
+    /// Whether a black mask or any slayer helmet is worn, and if so whether it is imbued.
+    fn slayer_headgear(&self) -> Option<bool> {
+        let head = self.worn(&EquipmentSlot::HEAD)?;
+        let imbued = head.ends_with("(i)");
+        let name = head.trim_end_matches("(i)").trim_end().to_lowercase();
+        (name.starts_with("black mask") || name.ends_with("slayer helmet")).then_some(imbued)
+    }
+
+    /// Slayer headgear boosts melee by a sixth on task, and only the imbued versions
+    /// boost ranged and magic, by 15%.
+    pub fn regular_bonus(&self, context: &CombatContext, attack_type: &AttackType) -> f64 {
+        let on_task = context.slayer_task();
+        match (self.slayer_headgear(), attack_type) {
+            (Some(_), AttackType::STAB | AttackType::SLASH | AttackType::CRUSH) if on_task => {
                 7.0 / 6.0
             }
+            (Some(true), _) if on_task => 1.15,
             _ => 1.0,
         }
     }
 
-    pub fn undead_bonus(&self, on_task: bool) -> f64 {
-        match self.equipment.get(&EquipmentSlot::NECK) {
-            Some(Some(neck)) if neck.name == "Salve amulet" || neck.name == "Salve amulet(i)" => {
-                7.0 / 6.0
-            }
-            Some(Some(neck))
-                if neck.name == "Salve amulet (e)" || neck.name == "Salve amulet(ei)" =>
-            {
-                1.2
-            }
-            _ => self.regular_bonus(on_task),
+    /// Salve amulet boost against undead, where only the imbued amulets work with
+    /// ranged and magic.
+    fn salve_bonus(&self, attack_type: &AttackType) -> Option<f64> {
+        let melee = matches!(
+            attack_type,
+            AttackType::STAB | AttackType::SLASH | AttackType::CRUSH
+        );
+        match self.worn(&EquipmentSlot::NECK)? {
+            "Salve amulet" if melee => Some(7.0 / 6.0),
+            "Salve amulet (e)" if melee => Some(1.2),
+            "Salve amulet(i)" if !melee && *attack_type != AttackType::RANGED => Some(1.15),
+            "Salve amulet(i)" => Some(7.0 / 6.0),
+            "Salve amulet(ei)" => Some(1.2),
+            _ => None,
+        }
+    }
+
+    /// The salve amulet takes precedence over slayer headgear rather than stacking with it.
+    pub fn undead_bonus(&self, context: &CombatContext, attack_type: &AttackType) -> f64 {
+        self.salve_bonus(attack_type)
+            .unwrap_or_else(|| self.regular_bonus(context, attack_type))
+    }
+
+    /// Tumeken's shadow multiplies the magic bonuses of everything worn alongside it.
+    fn powered_staff_multiplier(&self, context: &CombatContext, style: &AttackType) -> isize {
+        match style {
+            AttackType::MAGIC => self
+                .weapon
+                .as_ref()
+                .and_then(|x| x.powered_staff())
+                .map_or(1, |x| x.equipment_multiplier(context)),
+            _ => 1,
         }
     }
 
-    pub fn attack_equipment_bonus(&self, style: &AttackType) -> isize {
+    pub fn attack_equipment_bonus(&self, context: &CombatContext, style: &AttackType) -> isize {
         let bonus: isize = self
             .equipment
             .values()
             .map(|y| y.as_ref().map_or(0, |x| x.equipment.attack_bonus(style)))
             .sum();
+        let total = bonus
+            + self
+                .weapon
+                .as_ref()
+                .map_or(0, |x| x.equipment.attack_bonus(style));
+        total * self.powered_staff_multiplier(context, style)
+    }
+
+    pub fn strength_equipment_bonus(&self, context: &CombatContext, style: &AttackType) -> isize {
+        // Ammunition only adds ranged strength when the weapon actually fires it
+        let uses_ammo = self
+            .weapon
+            .as_ref()
+            .is_some_and(|x| x.required_ammo().is_some());
+        let bonus: isize = self
+            .equipment
+            .iter()
+            .filter(|(k, _)| {
+                **k != EquipmentSlot::AMMO || *style != AttackType::RANGED || uses_ammo
+            })
+            .map(|(_, y)| y.as_ref().map_or(0, |x| x.equipment.strength_bonus(style)))
+            .sum();
+        let total = bonus
+            + self
+                .weapon
+                .as_ref()
+                .map_or(0, |x| x.equipment.strength_bonus(style));
+        total * self.powered_staff_multiplier(context, style)
+    }
+
+    pub fn defence_equipment_bonus(&self, style: &AttackType) -> isize {
+        let bonus: isize = self
+            .equipment
+            .values()
+            .map(|y| y.as_ref().map_or(0, |x| x.equipment.defence_bonus(style)))
+            .sum();
         bonus
             + self
                 .weapon
                 .as_ref()
-                .map_or(0, |x| x.equipment.attack_bonus(style))
+                .map_or(0, |x| x.equipment.defence_bonus(style))
     }
 
-    pub fn strength_equipment_bonus(&self, style: &AttackType) -> isize {
+    pub fn prayer_bonus(&self) -> isize {
         let bonus: isize = self
             .equipment
             .values()
-            .map(|y| y.as_ref().map_or(0, |x| x.equipment.strength_bonus(style)))
+            .map(|y| y.as_ref().map_or(0, |x| x.equipment.prayer_bonus()))
             .sum();
         bonus
             + self
                 .weapon
                 .as_ref()
-                .map_or(0, |x| x.equipment.strength_bonus(style))
+                .map_or(0, |x| x.equipment.prayer_bonus())
     }
 
     pub fn attack_interval(&self) -> f64 {
@@ -349,45 +615,149 @@ impl Gear {
             .as_ref()
             .map_or(4.0 * 0.6, |x| x.attack_interval())
     }
+
+    /// Seconds between casts, which is five ticks regardless of the wielded weapon
+    /// unless the Harmonised orb is attached to the Nightmare staff.
+    pub fn cast_interval(&self, spell: &Spell) -> f64 {
+        match &self.weapon {
+            Some(weapon)
+                if weapon.name == "Harmonised nightmare staff"
+                    && spell.spellbook == Spellbook::STANDARD =>
+            {
+                4.0 * 0.6
+            }
+            _ => 5.0 * 0.6,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Player {
     pub name: String,
-    attack: isize,
-    strength: isize,
-    attack_potion: AttackPotion,
-    attack_prayer: AttackPrayer,
-    strength_potion: StrengthPotion,
-    strength_prayer: StrengthPrayer,
+    pub levels: Levels,
+    boosts: Boosts,
+    protection: ProtectionPrayer,
+    pub spell: Option<Spell>,
+    /// Where and why the player is fighting.
+    pub context: CombatContext,
+    /// Style the player fights in while being attacked by another player.
+    stance: Option<(AttackStyle, AttackType)>,
+    /// Minutes since potions were drunk.
+    boost_minutes: isize,
     pub gear: Gear,
     pub equipment: SpareGear,
 }
 
 impl Player {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         name: &str,
-        attack: isize,
-        strength: isize,
+        levels: Levels,
         attack_potion: AttackPotion,
         attack_prayer: AttackPrayer,
         strength_potion: StrengthPotion,
         strength_prayer: StrengthPrayer,
+        ranged_potion: RangedPotion,
+        ranged_prayer: RangedPrayer,
+        magic_potion: MagicPotion,
+        magic_prayer: MagicPrayer,
         gear: Gear,
     ) -> Self {
         Player {
             name: String::from(name),
-            attack: attack,
-            strength: strength,
-            attack_potion: attack_potion,
-            attack_prayer: attack_prayer,
-            strength_potion: strength_potion,
-            strength_prayer: strength_prayer,
-            gear: gear,
+            levels,
+            boosts: Boosts {
+                attack_potion,
+                attack_prayer,
+                strength_potion,
+                strength_prayer,
+                ranged_potion,
+                ranged_prayer,
+                magic_potion,
+                magic_prayer,
+            },
+            spell: None,
+            context: CombatContext::default(),
+            stance: None,
+            protection: ProtectionPrayer::NONE,
+            boost_minutes: 0,
+            gear,
             equipment: SpareGear::new(),
         }
     }
 
+    /// Dharok's set scales damage with the share of hitpoints the player is missing.
+    fn dharok_bonus(&self) -> f64 {
+        let hitpoints = self.levels.hitpoints;
+        let missing = (hitpoints.base - hitpoints.current.clamp(1, hitpoints.base)) as f64;
+        1.0 + missing / 100.0 * hitpoints.base as f64 / 100.0
+    }
+
+    pub fn boosts(&self) -> Boosts {
+        self.boosts
+    }
+
+    /// The player with a different set of prayers and potions.
+    pub fn with_boosts(&self, boosts: &Boosts) -> Player {
+        Player {
+            boosts: *boosts,
+            ..self.clone()
+        }
+    }
+
+    pub fn protection(&self) -> ProtectionPrayer {
+        self.protection
+    }
+
+    /// The player fighting in `style` while being attacked as a target.
+    pub fn with_stance(&self, style: &(AttackStyle, AttackType)) -> Player {
+        Player {
+            stance: Some(*style),
+            ..self.clone()
+        }
+    }
+
+    /// The player with a different protection prayer.
+    pub fn with_protection(&self, protection: ProtectionPrayer) -> Player {
+        Player {
+            protection,
+            ..self.clone()
+        }
+    }
+
+    /// Minutes since the potions were drunk, which wears off each boost the way that
+    /// potion decays. Drained levels, as from a Dragon battleaxe special, restore over time.
+    pub fn set_boost_minutes(&mut self, minutes: isize) {
+        self.boost_minutes = minutes;
+    }
+
+    /// Prayer points drained per minute by the active prayers, slowed by the prayer bonus.
+    pub fn prayer_drain_per_minute(&self) -> f64 {
+        let drain = prayer::drain_effect(&self.boosts) + self.protection.drain_effect();
+        prayer::drain_per_minute(drain, self.gear.prayer_bonus())
+    }
+
+    /// Minutes the active prayers can be kept up with the restores in the inventory.
+    pub fn prayer_minutes(&self, inventory: &Inventory) -> f64 {
+        prayer::prayer_minutes(
+            inventory.prayer_points(&self.levels.prayer),
+            self.prayer_drain_per_minute(),
+        )
+    }
+
+    /// Minutes until the food in the inventory runs out while fighting in `style`.
+    pub fn survival_minutes(
+        &self,
+        target: &impl Defender,
+        style: &(AttackStyle, AttackType),
+        inventory: &Inventory,
+    ) -> f64 {
+        prayer::survival_minutes(
+            inventory.hitpoints(&self.levels.hitpoints),
+            target.damage_per_second_to(self, style),
+        )
+    }
+
     fn strength_style_bonus(&self, attack_style: &AttackStyle) -> isize {
         match attack_style {
             AttackStyle::ACCURATE => 0,
@@ -399,18 +769,27 @@ impl Player {
     }
 
     pub fn weapon_styles(&self) -> Vec<(AttackStyle, AttackType)> {
-        self.gear
-            .weapon
+        let weapon = self.gear.weapon.clone().unwrap_or_default();
+        let can_cast = self
+            .spell
             .as_ref()
-            .map_or(_Weapon::default(), |x| x.weapon.clone())
+            .is_some_and(|x| x.castable_with(&weapon.name));
+        let powered = weapon.powered_staff().is_some();
+        weapon
+            .weapon
             .stances
             .iter()
-            .map(|x| (x.attack_style.unwrap(), x.attack_type.unwrap()))
+            .filter_map(|x| x.style())
+            .filter(|x| match x.1 {
+                AttackType::SPELLCASTING | AttackType::DEFENSIVECASTING => can_cast,
+                AttackType::MAGIC => powered,
+                _ => true,
+            })
             .collect()
     }
 
     fn strength_prayer_bonus(&self) -> f64 {
-        match &self.strength_prayer {
+        match &self.boosts.strength_prayer {
             StrengthPrayer::NONE => 1.0,
             StrengthPrayer::BURST => 1.05,
             StrengthPrayer::SUPERHUMAN => 1.1,
@@ -420,13 +799,35 @@ impl Player {
         }
     }
 
+    /// Strength gained from a Dragon battleaxe special, which drains a tenth of attack,
+    /// defence, ranged and magic and adds a quarter of that on top of ten levels.
+    fn dragon_battleaxe_bonus(&self) -> f64 {
+        let levels = &self.levels;
+        let drained = levels.attack.current / 10
+            + levels.defence.current / 10
+            + levels.ranged.current / 10
+            + levels.magic.current / 10;
+        (10 + drained / 4) as f64
+    }
+
+    fn decayed(&self, bonus: f64, decay: BoostDecay) -> isize {
+        decay.remaining(bonus.floor() as isize, self.boost_minutes)
+    }
+
     fn strength_potion_bonus(&self) -> isize {
-        let bonus = match &self.strength_potion {
+        let level = self.levels.strength.base as f64;
+        let bonus = match &self.boosts.strength_potion {
             StrengthPotion::NONE => 0.0,
-            StrengthPotion::STRENGTH => self.strength as f64 * 0.1 + 3.0,
-            StrengthPotion::SUPERSTRENGTH => self.strength as f64 * 0.15 + 5.0,
+            StrengthPotion::STRENGTH | StrengthPotion::COMBAT => level * 0.1 + 3.0,
+            StrengthPotion::SUPERSTRENGTH | StrengthPotion::DIVINESUPERSTRENGTH => {
+                level * 0.15 + 5.0
+            }
+            StrengthPotion::OVERLOAD => level * 0.16 + 6.0,
+            StrengthPotion::SMELLINGSALTS => level * 0.16 + 11.0,
+            StrengthPotion::ZAMORAKBREW => level * 0.12 + 2.0,
+            StrengthPotion::DRAGONBATTLEAXE => self.dragon_battleaxe_bonus(),
         };
-        bonus.floor() as isize
+        self.decayed(bonus, self.boosts.strength_potion.decay())
     }
 
     fn attack_style_bonus(&self, attack_style: &AttackStyle) -> isize {
@@ -440,7 +841,7 @@ impl Player {
     }
 
     fn attack_prayer_bonus(&self) -> f64 {
-        match &self.attack_prayer {
+        match &self.boosts.attack_prayer {
             AttackPrayer::NONE => 1.0,
             AttackPrayer::CLARITY => 1.05,
             AttackPrayer::IMPROVED => 1.1,
@@ -451,119 +852,747 @@ impl Player {
     }
 
     fn attack_potion_bonus(&self) -> isize {
-        let bonus = match &self.attack_potion {
+        let level = self.levels.attack.base as f64;
+        let bonus = match &self.boosts.attack_potion {
             AttackPotion::NONE => 0.0,
-            AttackPotion::ATTACK => self.attack as f64 * 0.1 + 3.0,
-            AttackPotion::SUPERATTACK => self.attack as f64 * 0.15 + 5.0,
+            AttackPotion::ATTACK | AttackPotion::COMBAT => level * 0.1 + 3.0,
+            AttackPotion::SUPERATTACK | AttackPotion::DIVINESUPERATTACK => level * 0.15 + 5.0,
+            AttackPotion::OVERLOAD => level * 0.16 + 6.0,
+            AttackPotion::SMELLINGSALTS => level * 0.16 + 11.0,
+            AttackPotion::ZAMORAKBREW => level * 0.2 + 2.0,
+            AttackPotion::DRAGONBATTLEAXE => -(self.levels.attack.current as f64 * 0.1).floor(),
+        };
+        self.decayed(bonus, self.boosts.attack_potion.decay())
+    }
+
+    fn ranged_style_bonus(&self, attack_style: &AttackStyle) -> isize {
+        match attack_style {
+            AttackStyle::ACCURATE => 3,
+            AttackStyle::RAPID => 0,
+            AttackStyle::LONGRANGE => 0,
+            _ => 0,
+        }
+    }
+
+    fn ranged_attack_prayer_bonus(&self) -> f64 {
+        match &self.boosts.ranged_prayer {
+            RangedPrayer::NONE => 1.0,
+            RangedPrayer::SHARPEYE => 1.05,
+            RangedPrayer::HAWKEYE => 1.1,
+            RangedPrayer::EAGLEEYE => 1.15,
+            RangedPrayer::RIGOUR => 1.2,
+        }
+    }
+
+    fn ranged_strength_prayer_bonus(&self) -> f64 {
+        match &self.boosts.ranged_prayer {
+            RangedPrayer::NONE => 1.0,
+            RangedPrayer::SHARPEYE => 1.05,
+            RangedPrayer::HAWKEYE => 1.1,
+            RangedPrayer::EAGLEEYE => 1.15,
+            RangedPrayer::RIGOUR => 1.23,
+        }
+    }
+
+    fn ranged_potion_bonus(&self) -> isize {
+        let level = self.levels.ranged.base as f64;
+        let bonus = match &self.boosts.ranged_potion {
+            RangedPotion::NONE => 0.0,
+            RangedPotion::RANGING | RangedPotion::DIVINERANGING => level * 0.1 + 4.0,
+            RangedPotion::SUPERRANGING => level * 0.15 + 5.0,
+            RangedPotion::OVERLOAD => level * 0.16 + 6.0,
+            RangedPotion::SMELLINGSALTS => level * 0.16 + 11.0,
+        };
+        self.decayed(bonus, self.boosts.ranged_potion.decay())
+    }
+
+    fn magic_style_bonus(&self, attack_style: &AttackStyle) -> isize {
+        match attack_style {
+            AttackStyle::ACCURATE => 2,
+            _ => 0,
+        }
+    }
+
+    fn magic_prayer_bonus(&self) -> f64 {
+        match &self.boosts.magic_prayer {
+            MagicPrayer::NONE => 1.0,
+            MagicPrayer::MYSTICWILL => 1.05,
+            MagicPrayer::MYSTICLORE => 1.1,
+            MagicPrayer::MYSTICMIGHT => 1.15,
+            MagicPrayer::AUGURY => 1.25,
+        }
+    }
+
+    fn magic_potion_bonus(&self) -> isize {
+        let level = self.levels.magic.base as f64;
+        let bonus = match &self.boosts.magic_potion {
+            MagicPotion::NONE => 0.0,
+            MagicPotion::MAGIC | MagicPotion::DIVINEMAGIC => 4.0,
+            MagicPotion::SUPERMAGIC => level * 0.15 + 5.0,
+            MagicPotion::OVERLOAD => level * 0.16 + 6.0,
+            MagicPotion::SMELLINGSALTS => level * 0.16 + 11.0,
+            MagicPotion::IMBUEDHEART => level * 0.1 + 1.0,
+            MagicPotion::SATURATEDHEART => level * 0.1 + 4.0,
         };
+        self.decayed(bonus, self.boosts.magic_potion.decay())
+    }
+
+    fn boosted_magic_level(&self) -> isize {
+        self.levels.magic.current + self.magic_potion_bonus()
+    }
+
+    fn effective_magic_level(&self, attack_style: &AttackStyle) -> isize {
+        let prayer = self.boosted_magic_level() as f64 * self.magic_prayer_bonus();
+        let style = prayer.floor() as isize + self.magic_style_bonus(attack_style) + 9;
+        let bonus = style as f64 * self.gear.magic_void_accuracy_bonus();
         bonus.floor() as isize
     }
 
+    /// Max hit of a spell, scaled by the magic damage bonus of the worn gear.
+    fn magic_max_hit(&self, attack_type: &AttackType) -> isize {
+        let base = match attack_type {
+            AttackType::SPELLCASTING | AttackType::DEFENSIVECASTING => self
+                .spell
+                .as_ref()
+                .map_or(0, |x| x.base_max_hit(self.boosted_magic_level())),
+            AttackType::MAGIC => self
+                .gear
+                .weapon
+                .as_ref()
+                .and_then(|x| x.powered_staff())
+                .map_or(0, |x| x.base_max_hit(self.boosted_magic_level())),
+            _ => 0,
+        };
+        let bonus = self
+            .gear
+            .strength_equipment_bonus(&self.context, attack_type) as f64
+            + self.gear.magic_void_damage_bonus();
+        let damage = 1.0 + bonus / 100.0;
+        (base as f64 * damage).floor() as isize
+    }
+
     fn effective_strength_level(&self, attack_style: &AttackStyle) -> isize {
-        let potion = self.strength + self.strength_potion_bonus();
+        let potion = self.levels.strength.current + self.strength_potion_bonus();
         let prayer = potion as f64 * self.strength_prayer_bonus();
         let style = prayer.floor() as isize + self.strength_style_bonus(attack_style) + 8;
-        let bonus = style as f64 * self.gear.void_bonus();
+        let bonus = style as f64 * self.gear.melee_void_bonus();
         bonus.floor() as isize
     }
 
     fn effective_attack_level(&self, attack_style: &AttackStyle) -> isize {
-        let potion = self.attack + self.attack_potion_bonus();
+        let potion = self.levels.attack.current + self.attack_potion_bonus();
         let prayer = potion as f64 * self.attack_prayer_bonus();
         let style = prayer.floor() as isize + self.attack_style_bonus(attack_style) + 8;
-        let bonus = style as f64 * self.gear.void_bonus();
+        let bonus = style as f64 * self.gear.melee_void_bonus();
+        bonus.floor() as isize
+    }
+
+    fn boosted_ranged_level(&self) -> isize {
+        self.levels.ranged.current + self.ranged_potion_bonus()
+    }
+
+    fn effective_ranged_strength_level(&self, attack_style: &AttackStyle) -> isize {
+        let potion = self.boosted_ranged_level();
+        let prayer = potion as f64 * self.ranged_strength_prayer_bonus();
+        let style = prayer.floor() as isize + self.ranged_style_bonus(attack_style) + 8;
+        let bonus = style as f64 * self.gear.ranged_void_strength_bonus();
+        bonus.floor() as isize
+    }
+
+    fn effective_ranged_attack_level(&self, attack_style: &AttackStyle) -> isize {
+        let potion = self.boosted_ranged_level();
+        let prayer = potion as f64 * self.ranged_attack_prayer_bonus();
+        let style = prayer.floor() as isize + self.ranged_style_bonus(attack_style) + 8;
+        let bonus = style as f64 * self.gear.ranged_void_accuracy_bonus();
         bonus.floor() as isize
     }
 
     pub fn max_hit(
         &self,
-        monster: &Monster,
-        on_task: bool,
+        target: &impl Defender,
         attack_style: &AttackStyle,
         attack_type: &AttackType,
     ) -> isize {
-        let hit = 0.5
-            + self.effective_strength_level(attack_style) as f64
-                * (self.gear.strength_equipment_bonus(&attack_type) + 64) as f64
-                / 640.0;
-        let after_bonus = match monster.is_undead() {
-            false => hit.floor() * self.gear.regular_bonus(on_task),
-            true => hit.floor() * self.gear.undead_bonus(on_task),
+        let hit = match attack_type {
+            AttackType::MAGIC | AttackType::SPELLCASTING | AttackType::DEFENSIVECASTING => {
+                self.magic_max_hit(attack_type) as f64
+            }
+            _ => {
+                let effective_level = match attack_type {
+                    AttackType::RANGED => self.effective_ranged_strength_level(attack_style),
+                    _ => self.effective_strength_level(attack_style),
+                };
+                let hit = 0.5
+                    + effective_level as f64
+                        * (self
+                            .gear
+                            .strength_equipment_bonus(&self.context, attack_type)
+                            + 64) as f64
+                        / 640.0;
+                hit.floor()
+            }
         };
-        after_bonus.floor() as isize
+        let after_bonus = hit * self.monster_bonus(target, attack_type);
+        let set_bonus = after_bonus.floor()
+            * self.gear.set_damage_bonus(attack_type)
+            * self.passive_damage_bonus(target, attack_type);
+        if self.gear.has_set(SetEffect::DHAROK) && attack_type != &AttackType::RANGED {
+            return (set_bonus.floor() * self.dharok_bonus()).floor() as isize;
+        }
+        set_bonus.floor() as isize
     }
 
     pub fn max_attack_roll(
         &self,
-        monster: &Monster,
-        on_task: bool,
+        target: &impl Defender,
         attack_style: &AttackStyle,
         attack_type: &AttackType,
     ) -> isize {
-        let roll = self.effective_attack_level(attack_style)
-            * (self.gear.attack_equipment_bonus(attack_type) + 64);
-        let after_bonus = match monster.is_undead() {
-            false => roll as f64 * self.gear.regular_bonus(on_task),
-            true => roll as f64 * self.gear.undead_bonus(on_task),
+        let effective_level = match attack_type {
+            AttackType::RANGED => self.effective_ranged_attack_level(attack_style),
+            AttackType::MAGIC | AttackType::SPELLCASTING | AttackType::DEFENSIVECASTING => {
+                self.effective_magic_level(attack_style)
+            }
+            _ => self.effective_attack_level(attack_style),
         };
-        after_bonus.floor() as isize
+        let roll =
+            effective_level * (self.gear.attack_equipment_bonus(&self.context, attack_type) + 64);
+        let after_bonus = roll as f64 * self.monster_bonus(target, attack_type);
+        let bonus = self.gear.set_accuracy_bonus(attack_type)
+            * self.passive_accuracy_bonus(target, attack_type);
+        (after_bonus.floor() * bonus).floor() as isize
     }
 
-    pub fn hit_chance(
-        &self,
-        monster: &Monster,
-        on_task: bool,
-        style: &(AttackStyle, AttackType),
-    ) -> f64 {
-        let attack = self.max_attack_roll(monster, on_task, &style.0, &style.1) as f64;
-        let defence = monster.max_defence_roll(&style.1) as f64;
-
-        if attack > defence {
-            1.0 - (defence + 2.0) / (2.0 * (attack + 1.0))
+    /// Slayer headgear or salve amulet multiplier, neither of which works on players.
+    fn monster_bonus(&self, target: &impl Defender, attack_type: &AttackType) -> f64 {
+        if target.is_player() {
+            1.0
+        } else if target.is_undead() {
+            self.gear.undead_bonus(&self.context, attack_type)
         } else {
-            attack / (2.0 * defence + 1.0)
+            self.gear.regular_bonus(&self.context, attack_type)
         }
     }
 
-    pub fn dps(&self, monster: &Monster, on_task: bool, style: &(AttackStyle, AttackType)) -> f64 {
-        self.hit_chance(monster, on_task, style)
-            * (self.max_hit(monster, on_task, &style.0, &style.1) as f64 / 2.0)
-            / self.gear.attack_interval()
+    /// The weapon, or the spell while casting, that passive effects are keyed on.
+    fn attack_source(&self, attack_type: &AttackType) -> &str {
+        match (attack_type, &self.spell) {
+            (AttackType::SPELLCASTING | AttackType::DEFENSIVECASTING, Some(spell)) => spell.name,
+            _ => self.gear.weapon.as_ref().map_or("", |x| x.name.as_str()),
+        }
     }
-}
 
-#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
-#[serde(rename_all = "lowercase")]
-pub enum AttackType {
-    STAB,
-    SLASH,
-    CRUSH,
-    RANGED,
-    MAGIC,
-    SPELLCASTING,
-    #[serde(rename = "defensive casting")]
-    DEFENSIVECASTING,
-}
+    /// Twisted bow (accuracy, damage) multipliers, or none for any other attack.
+    fn twisted_bow_bonus(&self, target: &impl Defender, attack_type: &AttackType) -> (f64, f64) {
+        match attack_type {
+            AttackType::RANGED if self.attack_source(attack_type).starts_with("Twisted bow") => {
+                passive::twisted_bow_bonus(target.highest_magic(), self.context.in_chambers())
+            }
+            _ => (1.0, 1.0),
+        }
+    }
 
-#[derive(Deserialize, Debug, Clone)]
-pub struct Monster {
-    pub name: String,
-    defence_level: isize,
-    defence_stab: isize,
-    defence_slash: isize,
-    defence_crush: isize,
-    defence_magic: isize,
-    defence_ranged: isize,
-    attributes: Vec<String>,
-}
+    fn wilderness_bonus(&self, target: &impl Defender, attack_type: &AttackType) -> f64 {
+        passive::wilderness_bonus(
+            self.attack_source(attack_type),
+            target,
+            self.context.wilderness,
+        )
+    }
 
-impl Monster {
-    fn effective_defence_level(&self) -> isize {
-        self.defence_level + 1 + 8
+    fn passive_accuracy_bonus(&self, target: &impl Defender, attack_type: &AttackType) -> f64 {
+        passive::passives(self.attack_source(attack_type), target)
+            .map(|x| x.accuracy)
+            .product::<f64>()
+            * self.twisted_bow_bonus(target, attack_type).0
+            * self.wilderness_bonus(target, attack_type)
     }
 
-    fn defence_equipment_bonus(&self, attack_type: &AttackType) -> isize {
-        match attack_type {
+    fn passive_damage_bonus(&self, target: &impl Defender, attack_type: &AttackType) -> f64 {
+        passive::passives(self.attack_source(attack_type), target)
+            .map(|x| x.damage)
+            .product::<f64>()
+            * self.twisted_bow_bonus(target, attack_type).1
+            * self.wilderness_bonus(target, attack_type)
+    }
+
+    /// Defence levels gained from the stance, where casting defensively counts as defensive.
+    fn defence_style_bonus(&self, style: &(AttackStyle, AttackType)) -> isize {
+        match style {
+            (_, AttackType::DEFENSIVECASTING) => 3,
+            (AttackStyle::DEFENSIVE | AttackStyle::LONGRANGE, _) => 3,
+            (AttackStyle::CONTROLLED, _) => 1,
+            _ => 0,
+        }
+    }
+
+    /// Chivalry, Piety, Rigour and Augury boost defence alongside their own skills.
+    fn defence_prayer_bonus(&self) -> f64 {
+        let melee: f64 = match &self.boosts.attack_prayer {
+            AttackPrayer::CHIVALRY => 1.2,
+            AttackPrayer::PIETY => 1.25,
+            _ => 1.0,
+        };
+        let ranged = match &self.boosts.ranged_prayer {
+            RangedPrayer::RIGOUR => 1.25,
+            _ => 1.0,
+        };
+        let magic = match &self.boosts.magic_prayer {
+            MagicPrayer::AUGURY => 1.25,
+            _ => 1.0,
+        };
+        melee.max(ranged).max(magic)
+    }
+
+    fn effective_defence_level(&self, style: &(AttackStyle, AttackType)) -> isize {
+        let prayer = self.levels.defence.current as f64 * self.defence_prayer_bonus();
+        prayer.floor() as isize + self.defence_style_bonus(style) + 8
+    }
+
+    /// Players defend against magic mostly with their magic level, and only partly with
+    /// their defence.
+    fn effective_magic_defence_level(&self, style: &(AttackStyle, AttackType)) -> isize {
+        let prayer = (self.boosted_magic_level() as f64 * self.magic_prayer_bonus()).floor();
+        let magic = (prayer * 0.7).floor() as isize;
+        let defence = (self.effective_defence_level(style) as f64 * 0.3).floor() as isize;
+        magic + defence
+    }
+
+    /// Defence roll against an incoming attack of `attack_type` while fighting in `style`.
+    pub fn stance_defence_roll(
+        &self,
+        attack_type: &AttackType,
+        style: &(AttackStyle, AttackType),
+    ) -> isize {
+        let effective_level = match attack_type {
+            AttackType::MAGIC | AttackType::SPELLCASTING | AttackType::DEFENSIVECASTING => {
+                self.effective_magic_defence_level(style)
+            }
+            _ => self.effective_defence_level(style),
+        };
+        effective_level * (self.gear.defence_equipment_bonus(attack_type) + 64)
+    }
+
+    /// Expected damage the monster deals per second while the player fights in `style`,
+    /// where every hit is uniform between zero and the monster's max hit. The protection
+    /// prayer blocks attacks of its type, but not prayer-piercing ones.
+    pub fn damage_taken_per_second(
+        &self,
+        monster: &Monster,
+        style: &(AttackStyle, AttackType),
+    ) -> f64 {
+        let average_hit = monster.max_hit() as f64 / 2.0;
+        let typed: f64 = monster
+            .attack_types()
+            .iter()
+            .filter(|(attack_type, _)| !self.protection.protects(attack_type))
+            .map(|(attack_type, weight)| {
+                let defence = self.stance_defence_roll(attack_type, style);
+                let chance = hit_chance(monster.max_attack_roll(attack_type), defence);
+                weight * chance * average_hit
+            })
+            .sum();
+        let piercing = monster.piercing_share() * average_hit;
+        (typed + piercing) / monster.attack_interval()
+    }
+
+    pub fn hit_chance(&self, target: &impl Defender, style: &(AttackStyle, AttackType)) -> f64 {
+        let attack = self.max_attack_roll(target, &style.0, &style.1);
+        hit_chance(attack, target.max_defence_roll(&style.1))
+    }
+
+    /// Seconds between attacks, where the rapid stance shaves a tick off the weapon speed
+    /// and cast spells follow their own speed.
+    pub fn attack_interval(&self, style: &(AttackStyle, AttackType)) -> f64 {
+        match style {
+            (_, AttackType::SPELLCASTING | AttackType::DEFENSIVECASTING) => self
+                .spell
+                .as_ref()
+                .map_or(self.gear.attack_interval(), |x| self.gear.cast_interval(x)),
+            (AttackStyle::RAPID, _) => self.gear.attack_interval() - 0.6,
+            _ => self.gear.attack_interval(),
+        }
+    }
+
+    /// Damage dealt by a single attack, including weapons that hit several times and
+    /// effects that proc on a fraction of attacks.
+    pub fn hit_distribution(
+        &self,
+        target: &impl Defender,
+        style: &(AttackStyle, AttackType),
+    ) -> HitDistribution {
+        if style.1 == AttackType::RANGED && !self.gear.has_required_ammo() {
+            return HitDistribution::miss();
+        }
+        let ammo = self.gear.ammo().map_or("", |x| x.name.as_str());
+        if !passive::can_damage(target, &style.1, self.attack_source(&style.1), ammo) {
+            return HitDistribution::miss();
+        }
+        let accuracy = self.hit_chance(target, style);
+        let max_hit = self.max_hit(target, &style.0, &style.1);
+        let weapon = self.gear.weapon.clone().unwrap_or_default();
+        let hit = HitDistribution::linear(accuracy, max_hit);
+
+        // Barrows sets whose effect procs on a quarter of attacks
+        let barrows = self
+            .gear
+            .set_effects()
+            .iter()
+            .map(|x| x.effect)
+            .find(|x| matches!(x, SetEffect::VERAC | SetEffect::KARIL | SetEffect::AHRIM));
+        let hit = match barrows {
+            Some(SetEffect::VERAC) => {
+                let proc = HitDistribution::linear(1.0, max_hit).transform(|x| x + 1);
+                hit.mix(&proc, 0.25)
+            }
+            // The proc adds a second hit that rolls separately for half the max hit
+            Some(SetEffect::KARIL) if style.1 == AttackType::RANGED => hit.mix(
+                &hit.combine(&HitDistribution::linear(accuracy, max_hit / 2)),
+                0.25,
+            ),
+            Some(SetEffect::AHRIM)
+                if matches!(
+                    style.1,
+                    AttackType::MAGIC | AttackType::SPELLCASTING | AttackType::DEFENSIVECASTING
+                ) =>
+            {
+                hit.mix(&HitDistribution::linear(accuracy, max_hit * 13 / 10), 0.25)
+            }
+            _ => hit,
+        };
+
+        let hit = match (weapon.name.as_str(), weapon.required_ammo()) {
+            // Some hits against kalphites deal triple damage
+            (name, _) if name.starts_with("Keris") && target.has_attribute("kalphite") => {
+                hit.mix(&hit.scale(3.0), KERIS_TRIPLE_CHANCE)
+            }
+            // Each further hit deals half the damage of the previous, one hit per tile of size
+            (name, _) if name.starts_with("Scythe of vitur") => (1..target.size().min(3))
+                .fold(hit, |acc, x| {
+                    acc.combine(&HitDistribution::linear(accuracy, max_hit >> x))
+                }),
+            (_, Some("bolts")) if ammo.starts_with("Diamond") && ammo.ends_with("(e)") => {
+                let proc = HitDistribution::linear(1.0, max_hit * 115 / 100);
+                hit.mix(&proc, 0.1)
+            }
+            (_, Some("bolts")) if ammo.starts_with("Opal") && ammo.ends_with("(e)") => {
+                let bonus = self.boosted_ranged_level() / 10;
+                let proc = HitDistribution::linear(1.0, max_hit).transform(|x| x + bonus);
+                hit.mix(&proc, 0.05)
+            }
+            _ => hit,
+        };
+        protected(hit, target, &style.1)
+    }
+
+    /// Damage of the wielded weapon's special attack, if it has one.
+    pub fn special_distribution(
+        &self,
+        target: &impl Defender,
+        style: &(AttackStyle, AttackType),
+    ) -> Option<HitDistribution> {
+        let special = SpecialAttack::for_weapon(&self.gear.weapon.as_ref()?.name)?;
+        if style.1 == AttackType::RANGED && !self.gear.has_required_ammo() {
+            return Some(HitDistribution::miss());
+        }
+        let ammo = self.gear.ammo().map_or("", |x| x.name.as_str());
+        if !passive::can_damage(target, &style.1, self.attack_source(&style.1), ammo) {
+            return Some(HitDistribution::miss());
+        }
+        let attack = self.max_attack_roll(target, &style.0, &style.1) as f64;
+        let accuracy = hit_chance(
+            (attack * special.accuracy).floor() as isize,
+            target.max_defence_roll(&style.1),
+        );
+        let max_hit = self.max_hit(target, &style.0, &style.1) as f64;
+        let hit = special.distribution(accuracy, (max_hit * special.damage).floor() as isize);
+        Some(protected(hit, target, &style.1))
+    }
+
+    /// The player swapped to `weapon`, taking off the shield for two-handed weapons.
+    pub fn with_weapon(&self, weapon: &Weapon) -> Player {
+        let mut player = self.clone();
+        if weapon.equipment.slot == EquipmentSlot::TWOHAND {
+            player.gear.add_equipment(&EquipmentSlot::SHIELD, None);
+        }
+        player.gear.add_weapon(Some(weapon.clone()));
+        player
+    }
+
+    pub fn dps(&self, target: &impl Defender, style: &(AttackStyle, AttackType)) -> f64 {
+        self.hit_distribution(target, style).expected() / self.attack_interval(style)
+    }
+
+    /// Damage per second averaged over every minute of a trip as the potions wear off.
+    pub fn trip_dps(
+        &self,
+        target: &impl Defender,
+        style: &(AttackStyle, AttackType),
+        trip: &Trip,
+    ) -> f64 {
+        let mut player = self.clone();
+        let mut cache: HashMap<isize, f64> = HashMap::new();
+        let dps: Vec<f64> = trip
+            .dose_ages()
+            .map(|minutes| {
+                *cache.entry(minutes).or_insert_with(|| {
+                    player.set_boost_minutes(minutes);
+                    player.dps(target, style)
+                })
+            })
+            .collect();
+        dps.iter().sum::<f64>() / dps.len() as f64
+    }
+
+    /// Expected seconds to kill the target from full hitpoints, accounting for overkill.
+    pub fn ttk(&self, target: &impl Defender, style: &(AttackStyle, AttackType)) -> f64 {
+        self.hit_distribution(target, style)
+            .expected_time_to_kill(target.hitpoints(), self.attack_interval(style))
+    }
+}
+
+impl Defender for Player {
+    /// Defence roll in the player's stance, where a player without one gets no stance bonus.
+    fn max_defence_roll(&self, attack_type: &AttackType) -> isize {
+        let stance = self
+            .stance
+            .unwrap_or((AttackStyle::ACCURATE, AttackType::CRUSH));
+        self.stance_defence_roll(attack_type, &stance)
+    }
+
+    fn has_attribute(&self, _attribute: &str) -> bool {
+        false
+    }
+
+    fn hitpoints(&self) -> isize {
+        self.levels.hitpoints.current
+    }
+
+    fn size(&self) -> isize {
+        1
+    }
+
+    fn highest_magic(&self) -> isize {
+        self.boosted_magic_level().max(
+            self.gear
+                .attack_equipment_bonus(&self.context, &AttackType::MAGIC),
+        )
+    }
+
+    /// Protection prayers block 40% of the damage from other players.
+    fn damage_multiplier(&self, attack_type: &AttackType) -> f64 {
+        if self.protection.protects(attack_type) {
+            0.6
+        } else {
+            1.0
+        }
+    }
+
+    fn is_player(&self) -> bool {
+        true
+    }
+}
+
+/// Damage of `hit` after the target's protection against `attack_type`.
+fn protected(
+    hit: HitDistribution,
+    target: &impl Defender,
+    attack_type: &AttackType,
+) -> HitDistribution {
+    match target.damage_multiplier(attack_type) {
+        multiplier if multiplier < 1.0 => hit.scale(multiplier),
+        _ => hit,
+    }
+}
+
+/// Chance that an attack roll beats a defence roll.
+fn hit_chance(attack: isize, defence: isize) -> f64 {
+    let (attack, defence) = (attack as f64, defence as f64);
+    if attack > defence {
+        1.0 - (defence + 2.0) / (2.0 * (attack + 1.0))
+    } else {
+        attack / (2.0 * defence + 1.0)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum AttackType {
+    STAB,
+    SLASH,
+    CRUSH,
+    RANGED,
+    MAGIC,
+    SPELLCASTING,
+    #[serde(rename = "defensive casting")]
+    DEFENSIVECASTING,
+}
+
+/// The side of the combat triangle an attack type belongs to.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum CombatClass {
+    MELEE,
+    RANGED,
+    MAGIC,
+}
+
+impl AttackType {
+    pub fn class(&self) -> CombatClass {
+        match self {
+            AttackType::STAB | AttackType::SLASH | AttackType::CRUSH => CombatClass::MELEE,
+            AttackType::RANGED => CombatClass::RANGED,
+            AttackType::MAGIC | AttackType::SPELLCASTING | AttackType::DEFENSIVECASTING => {
+                CombatClass::MAGIC
+            }
+        }
+    }
+}
+
+/// Reads a null in the monster file as the default, as the monster data leaves stats
+/// such as the max hit and attack speed null when they are unknown.
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Monster {
+    pub name: String,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub hitpoints: isize,
+    defence_level: isize,
+    magic_level: isize,
+    attack_magic: isize,
+    size: isize,
+    defence_stab: isize,
+    defence_slash: isize,
+    defence_crush: isize,
+    defence_magic: isize,
+    defence_ranged: isize,
+    #[serde(default, deserialize_with = "null_as_default")]
+    attack_level: isize,
+    #[serde(default, deserialize_with = "null_as_default")]
+    strength_level: isize,
+    #[serde(default, deserialize_with = "null_as_default")]
+    ranged_level: isize,
+    /// Melee accuracy bonus.
+    #[serde(default, deserialize_with = "null_as_default")]
+    attack_bonus: isize,
+    /// Melee strength bonus.
+    #[serde(default, deserialize_with = "null_as_default")]
+    strength_bonus: isize,
+    #[serde(default, deserialize_with = "null_as_default")]
+    attack_ranged: isize,
+    /// Ranged strength bonus.
+    #[serde(default, deserialize_with = "null_as_default")]
+    ranged_bonus: isize,
+    /// Magic damage bonus.
+    #[serde(default, deserialize_with = "null_as_default")]
+    magic_bonus: isize,
+    #[serde(default, deserialize_with = "null_as_default")]
+    max_hit: isize,
+    /// Ticks between attacks.
+    #[serde(default, deserialize_with = "null_as_default")]
+    attack_speed: isize,
+    /// How the monster attacks, such as "slash", "magic" or just "melee".
+    #[serde(default, deserialize_with = "null_as_default")]
+    attack_type: Vec<String>,
+    attributes: Vec<String>,
+    /// Defence levels lost to draining special attacks during the current fight.
+    #[serde(skip)]
+    defence_drained: isize,
+}
+
+/// Bosses whose defence cannot be drained below a given level.
+const DEFENCE_FLOORS: &[(&str, isize)] =
+    &[("Akkha", 70), ("Ba-Ba", 60), ("Kephri", 60), ("Zebak", 50)];
+
+impl Monster {
+    pub fn defence_level(&self) -> isize {
+        self.defence_level - self.defence_drained
+    }
+
+    pub fn defence_drained(&self) -> isize {
+        self.defence_drained
+    }
+
+    fn min_defence_level(&self) -> isize {
+        DEFENCE_FLOORS
+            .iter()
+            .find(|x| x.0 == self.name)
+            .map_or(0, |x| x.1.min(self.defence_level))
+    }
+
+    /// Applies the defence drain of a special attack that dealt `damage`.
+    pub fn drain_defence(&mut self, effect: &SpecialEffect, damage: isize) {
+        let current = self.defence_level();
+        let drain = match effect {
+            SpecialEffect::DRAINDAMAGE => damage,
+            SpecialEffect::DRAINPERCENT(percent) if damage > 0 => current * percent / 100,
+            SpecialEffect::DRAINBASEPERCENT(percent) if damage > 0 => {
+                let percent = if self.is_demon() {
+                    percent * 2
+                } else {
+                    *percent
+                };
+                self.defence_level * percent / 100
+            }
+            _ => 0,
+        };
+        let drained = (current - drain).max(self.min_defence_level());
+        self.defence_drained = self.defence_level - drained.min(current);
+    }
+
+    pub fn set_defence_drained(&mut self, levels: isize) {
+        self.defence_drained = levels;
+    }
+
+    /// Restores the monster to its spawn state.
+    pub fn reset(&mut self) {
+        self.defence_drained = 0;
+    }
+
+    /// The monster with raid scaling applied to its hitpoints, its defence level, its
+    /// attack, ranged and magic levels for accuracy and its strength for damage. The max
+    /// hit grows along with the effective level of its strongest damage skill.
+    pub fn rescaled(
+        &self,
+        hitpoints: impl Fn(isize) -> isize,
+        defence: impl Fn(isize) -> isize,
+        accuracy: impl Fn(isize) -> isize,
+        damage: impl Fn(isize) -> isize,
+    ) -> Monster {
+        let damage_level = self
+            .strength_level
+            .max(self.ranged_level)
+            .max(self.magic_level);
+        Monster {
+            hitpoints: hitpoints(self.hitpoints),
+            defence_level: defence(self.defence_level),
+            attack_level: accuracy(self.attack_level),
+            strength_level: damage(self.strength_level),
+            ranged_level: accuracy(self.ranged_level),
+            magic_level: accuracy(self.magic_level),
+            max_hit: self.max_hit * (damage(damage_level) + 9) / (damage_level + 9),
+            ..self.clone()
+        }
+    }
+
+    fn effective_defence_level(&self) -> isize {
+        self.defence_level() + 1 + 8
+    }
+
+    /// Monsters defend against magic with their magic level rather than their defence.
+    fn effective_magic_defence_level(&self) -> isize {
+        self.magic_level + 1 + 8
+    }
+
+    fn defence_equipment_bonus(&self, attack_type: &AttackType) -> isize {
+        match attack_type {
             AttackType::STAB => self.defence_stab,
             AttackType::SLASH => self.defence_slash,
             AttackType::CRUSH => self.defence_crush,
@@ -574,12 +1603,128 @@ impl Monster {
         }
     }
 
+    /// Attacks such as dragonfire and typeless hits that ignore defence and protection
+    /// prayers alike.
+    fn is_piercing(attack_type: &str) -> bool {
+        matches!(attack_type, "dragonfire" | "typeless")
+    }
+
+    /// Types of each non-piercing attack, where melee without a specific type is any of
+    /// stab, slash and crush.
+    fn typed_attacks(&self) -> Vec<Vec<AttackType>> {
+        self.attack_type
+            .iter()
+            .filter_map(|x| match x.as_str() {
+                "stab" => Some(vec![AttackType::STAB]),
+                "slash" => Some(vec![AttackType::SLASH]),
+                "crush" => Some(vec![AttackType::CRUSH]),
+                "melee" => Some(vec![AttackType::STAB, AttackType::SLASH, AttackType::CRUSH]),
+                "ranged" => Some(vec![AttackType::RANGED]),
+                "magic" => Some(vec![AttackType::MAGIC]),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn piercing_attacks(&self) -> usize {
+        self.attack_type
+            .iter()
+            .filter(|x| Monster::is_piercing(x))
+            .count()
+    }
+
+    /// Attack types the monster uses, each attack equally likely and melee without a
+    /// specific type split evenly. Prayer-piercing attacks are covered by `piercing_share`.
+    pub fn attack_types(&self) -> Vec<(AttackType, f64)> {
+        let types = self.typed_attacks();
+        let count = (types.len() + self.piercing_attacks()) as f64;
+        types
+            .iter()
+            .flat_map(|x| {
+                let weight = 1.0 / (count * x.len() as f64);
+                x.iter().map(move |y| (*y, weight))
+            })
+            .collect()
+    }
+
+    /// Share of the monster's attacks that always hit and go through protection prayers.
+    pub fn piercing_share(&self) -> f64 {
+        let piercing = self.piercing_attacks();
+        match piercing + self.typed_attacks().len() {
+            0 => 0.0,
+            total => piercing as f64 / total as f64,
+        }
+    }
+
+    fn effective_attack_level(&self, attack_type: &AttackType) -> isize {
+        let level = match attack_type {
+            AttackType::RANGED => self.ranged_level,
+            AttackType::MAGIC | AttackType::SPELLCASTING | AttackType::DEFENSIVECASTING => {
+                self.magic_level
+            }
+            _ => self.attack_level,
+        };
+        level + 1 + 8
+    }
+
+    fn attack_equipment_bonus(&self, attack_type: &AttackType) -> isize {
+        match attack_type {
+            AttackType::RANGED => self.attack_ranged,
+            AttackType::MAGIC | AttackType::SPELLCASTING | AttackType::DEFENSIVECASTING => {
+                self.attack_magic
+            }
+            _ => self.attack_bonus,
+        }
+    }
+
+    pub fn max_attack_roll(&self, attack_type: &AttackType) -> isize {
+        self.effective_attack_level(attack_type) * (self.attack_equipment_bonus(attack_type) + 64)
+    }
+
+    pub fn max_hit(&self) -> isize {
+        self.max_hit
+    }
+
+    /// Seconds between attacks, assuming the common four ticks when the speed is unknown.
+    pub fn attack_interval(&self) -> f64 {
+        let ticks = if self.attack_speed > 0 {
+            self.attack_speed
+        } else {
+            4
+        };
+        ticks as f64 * 0.6
+    }
+}
+
+impl Defender for Monster {
     fn max_defence_roll(&self, attack_type: &AttackType) -> isize {
-        self.effective_defence_level() * (self.defence_equipment_bonus(attack_type) + 64)
+        let effective_level = match attack_type {
+            AttackType::SPELLCASTING | AttackType::DEFENSIVECASTING | AttackType::MAGIC => {
+                self.effective_magic_defence_level()
+            }
+            _ => self.effective_defence_level(),
+        };
+        effective_level * (self.defence_equipment_bonus(attack_type) + 64)
     }
 
-    fn is_undead(&self) -> bool {
-        self.attributes.contains(&String::from("undead"))
+    fn has_attribute(&self, attribute: &str) -> bool {
+        self.attributes.iter().any(|x| x.starts_with(attribute))
+    }
+
+    fn hitpoints(&self) -> isize {
+        self.hitpoints
+    }
+
+    fn size(&self) -> isize {
+        self.size
+    }
+
+    fn highest_magic(&self) -> isize {
+        self.magic_level.max(self.attack_magic)
+    }
+
+    fn damage_per_second_to(&self, attacker: &Player, style: &(AttackStyle, AttackType)) -> f64 {
+        attacker.damage_taken_per_second(self, style)
     }
 }
 
@@ -590,6 +1735,35 @@ pub struct WeaponStance {
     pub attack_style: Option<AttackStyle>,
 }
 
+impl WeaponStance {
+    /// Resolves the stance into a style and type, filling in ranged stances that the
+    /// item database only names through their combat style.
+    pub fn style(&self) -> Option<(AttackStyle, AttackType)> {
+        let ranged_style = match self.combat_style.as_str() {
+            "accurate" | "short fuse" => Some(AttackStyle::ACCURATE),
+            "rapid" | "medium fuse" => Some(AttackStyle::RAPID),
+            "longrange" | "long fuse" => Some(AttackStyle::LONGRANGE),
+            _ => None,
+        };
+        match (self.attack_type, self.attack_style, ranged_style) {
+            (Some(AttackType::RANGED), _, Some(style)) | (None, _, Some(style)) => {
+                Some((style, AttackType::RANGED))
+            }
+            (Some(AttackType::MAGIC), _, style) => {
+                Some((style.unwrap_or(AttackStyle::MAGIC), AttackType::MAGIC))
+            }
+            (Some(AttackType::SPELLCASTING), _, _) => {
+                Some((AttackStyle::MAGIC, AttackType::SPELLCASTING))
+            }
+            (Some(AttackType::DEFENSIVECASTING), _, _) => {
+                Some((AttackStyle::MAGIC, AttackType::DEFENSIVECASTING))
+            }
+            (Some(attack_type), Some(attack_style), _) => Some((attack_style, attack_type)),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct _Weapon {
     pub attack_speed: isize,
@@ -621,6 +1795,36 @@ impl Default for _Weapon {
     }
 }
 
+/// Staves with a built-in spell whose max hit scales with the magic level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PoweredStaff {
+    TRIDENTOFTHESEAS,
+    TRIDENTOFTHESWAMP,
+    SANGUINESTI,
+    TUMEKENSSHADOW,
+}
+
+impl PoweredStaff {
+    pub fn base_max_hit(&self, magic_level: isize) -> isize {
+        match self {
+            PoweredStaff::TRIDENTOFTHESEAS => magic_level / 3 - 5,
+            PoweredStaff::TRIDENTOFTHESWAMP => magic_level / 3 - 2,
+            PoweredStaff::SANGUINESTI => magic_level / 3 - 1,
+            PoweredStaff::TUMEKENSSHADOW => magic_level / 3 + 1,
+        }
+    }
+
+    /// Tumeken's shadow multiplies the magic bonuses of the other equipment, even more so
+    /// inside the Tombs of Amascut.
+    pub fn equipment_multiplier(&self, context: &CombatContext) -> isize {
+        match self {
+            PoweredStaff::TUMEKENSSHADOW if context.in_tombs() => 4,
+            PoweredStaff::TUMEKENSSHADOW => 3,
+            _ => 1,
+        }
+    }
+}
+
 #[derive(Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Weapon {
     pub name: String,
@@ -649,17 +1853,489 @@ impl Weapon {
         self.weapon.attack_speed as f64 * 0.6
     }
 
+    pub fn powered_staff(&self) -> Option<PoweredStaff> {
+        match self.name.as_str() {
+            "Trident of the seas" | "Trident of the seas (e)" => {
+                Some(PoweredStaff::TRIDENTOFTHESEAS)
+            }
+            "Trident of the swamp" | "Trident of the swamp (e)" => {
+                Some(PoweredStaff::TRIDENTOFTHESWAMP)
+            }
+            "Sanguinesti staff" | "Holy sanguinesti staff" => Some(PoweredStaff::SANGUINESTI),
+            "Tumeken's shadow" => Some(PoweredStaff::TUMEKENSSHADOW),
+            _ => None,
+        }
+    }
+
+    /// The kind of ammunition the weapon fires from the ammo slot, if any.
+    fn required_ammo(&self) -> Option<&'static str> {
+        let name = self.name.as_str();
+        if name.starts_with("Crystal bow")
+            || name.starts_with("Bow of faerdhinen")
+            || name.starts_with("Craw's bow")
+            || name.starts_with("Webweaver bow")
+        {
+            None
+        } else if name.starts_with("Karil's crossbow") {
+            Some("bolt rack")
+        } else if name.contains("crossbow") {
+            Some("bolts")
+        } else if name.contains("ballista") {
+            Some("javelin")
+        } else if name.contains("bow") {
+            Some("arrow")
+        } else {
+            None
+        }
+    }
+
     fn attack_type(&self, attack_style: usize) -> &AttackType {
-        &self.weapon.stances[attack_style]
+        self.weapon.stances[attack_style]
             .attack_type
             .as_ref()
             .unwrap()
     }
 }
 
-/*
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::context::Raid;
+    use crate::fixtures;
+    use crate::levels::Level;
+
+    fn item(name: &str, slot: EquipmentSlot) -> Equipment {
+        Equipment {
+            name: String::from(name),
+            equipment: _Equipment {
+                slot,
+                .._Equipment::unarmed()
+            },
+        }
+    }
+
+    fn weapon(name: &str) -> Weapon {
+        Weapon {
+            name: String::from(name),
+            ..Weapon::default()
+        }
+    }
+
+    fn player(items: &[(&str, EquipmentSlot)]) -> Player {
+        let mut gear = Gear::empty();
+        for (name, slot) in items {
+            gear.add_equipment(slot, Some(item(name, *slot)));
+        }
+        fixtures::player_with(fixtures::levels(), gear)
+    }
+
+    fn void(helm: &str, top: &str, robe: &str) -> Player {
+        player(&[
+            (helm, EquipmentSlot::HEAD),
+            (top, EquipmentSlot::BODY),
+            (robe, EquipmentSlot::LEGS),
+            ("Void knight gloves", EquipmentSlot::HANDS),
+        ])
+    }
+
+    #[test]
+    fn no_void() {
+        let player = player(&[]);
+        assert_eq!(player.gear.melee_void_bonus(), 1.0);
+        assert_eq!(player.gear.ranged_void_accuracy_bonus(), 1.0);
+        assert_eq!(player.gear.ranged_void_strength_bonus(), 1.0);
+        assert_eq!(player.gear.magic_void_accuracy_bonus(), 1.0);
+        assert_eq!(player.gear.magic_void_damage_bonus(), 0.0);
+    }
+
+    #[test]
+    fn incomplete_void() {
+        let player = player(&[
+            ("Void melee helm", EquipmentSlot::HEAD),
+            ("Void knight top", EquipmentSlot::BODY),
+            ("Void knight robe", EquipmentSlot::LEGS),
+        ]);
+        assert_eq!(player.gear.melee_void_bonus(), 1.0);
+    }
+
+    #[test]
+    fn melee_void() {
+        let player = void("Void melee helm", "Void knight top", "Void knight robe");
+        assert_eq!(player.gear.melee_void_bonus(), 1.1);
+        assert_eq!(player.gear.ranged_void_accuracy_bonus(), 1.0);
+        assert_eq!(player.gear.ranged_void_strength_bonus(), 1.0);
+        assert_eq!(player.gear.magic_void_accuracy_bonus(), 1.0);
+        assert_eq!(player.effective_attack_level(&AttackStyle::ACCURATE), 121);
+        assert_eq!(
+            player.effective_strength_level(&AttackStyle::AGGRESSIVE),
+            121
+        );
+        assert_eq!(
+            player.effective_ranged_attack_level(&AttackStyle::ACCURATE),
+            110
+        );
+        assert_eq!(player.effective_magic_level(&AttackStyle::MAGIC), 108);
+    }
+
+    #[test]
+    fn elite_melee_void() {
+        let player = void("Void melee helm", "Elite void top", "Elite void robe");
+        assert_eq!(player.gear.melee_void_bonus(), 1.1);
+        assert_eq!(player.gear.ranged_void_strength_bonus(), 1.0);
+    }
+
+    #[test]
+    fn ranged_void() {
+        let player = void("Void ranger helm", "Void knight top", "Void knight robe");
+        assert_eq!(player.gear.melee_void_bonus(), 1.0);
+        assert_eq!(player.gear.magic_void_accuracy_bonus(), 1.0);
+        assert_eq!(
+            player.effective_ranged_attack_level(&AttackStyle::ACCURATE),
+            121
+        );
+        assert_eq!(
+            player.effective_ranged_strength_level(&AttackStyle::ACCURATE),
+            121
+        );
+        assert_eq!(player.effective_attack_level(&AttackStyle::ACCURATE), 110);
+    }
+
+    #[test]
+    fn elite_ranged_void() {
+        let player = void("Void ranger helm", "Elite void top", "Elite void robe");
+        assert_eq!(
+            player.effective_ranged_attack_level(&AttackStyle::ACCURATE),
+            121
+        );
+        assert_eq!(
+            player.effective_ranged_strength_level(&AttackStyle::ACCURATE),
+            123
+        );
+    }
+
+    #[test]
+    fn mixed_elite_pieces_are_regular_void() {
+        let player = void("Void ranger helm", "Elite void top", "Void knight robe");
+        assert_eq!(player.gear.ranged_void_strength_bonus(), 1.1);
+    }
+
+    #[test]
+    fn magic_void() {
+        let player = void("Void mage helm", "Void knight top", "Void knight robe");
+        assert_eq!(player.gear.melee_void_bonus(), 1.0);
+        assert_eq!(player.gear.ranged_void_accuracy_bonus(), 1.0);
+        assert_eq!(player.effective_magic_level(&AttackStyle::MAGIC), 156);
+        assert_eq!(player.gear.magic_void_damage_bonus(), 0.0);
+        assert_eq!(
+            player.effective_strength_level(&AttackStyle::AGGRESSIVE),
+            110
+        );
+    }
+
+    /// Ice Barrage max hit in magic void with 8% magic damage from elsewhere.
+    fn void_ice_barrage(top: &str, robe: &str) -> isize {
+        let mut player = void("Void mage helm", top, robe);
+        let mut cape = item("Test cape", EquipmentSlot::CAPE);
+        cape.equipment.magic_damage = 8;
+        player.gear.add_equipment(&EquipmentSlot::CAPE, Some(cape));
+        player.spell = Spell::from_name("Ice Barrage");
+        player.magic_max_hit(&AttackType::SPELLCASTING)
+    }
+
+    #[test]
+    fn elite_magic_void() {
+        let player = void("Void mage helm", "Elite void top", "Elite void robe");
+        assert_eq!(player.effective_magic_level(&AttackStyle::MAGIC), 156);
+        assert_eq!(player.gear.magic_void_damage_bonus(), 2.5);
+        assert_eq!(void_ice_barrage("Elite void top", "Elite void robe"), 33);
+        assert_eq!(void_ice_barrage("Void knight top", "Void knight robe"), 32);
+    }
+
+    #[test]
+    fn protection_prayer_against_players() {
+        let target = player(&[]).with_protection(ProtectionPrayer::MELEE);
+        assert_eq!(target.damage_multiplier(&AttackType::STAB), 0.6);
+        assert_eq!(target.damage_multiplier(&AttackType::RANGED), 1.0);
+    }
+
+    #[test]
+    fn slayer_helmet_against_players() {
+        let target = player(&[]);
+        let helmet = player(&[("Slayer helmet (i)", EquipmentSlot::HEAD)]);
+        let style = (AttackStyle::AGGRESSIVE, AttackType::CRUSH);
+        assert_eq!(
+            helmet.max_hit(&target, &style.0, &style.1),
+            player(&[]).max_hit(&target, &style.0, &style.1)
+        );
+    }
+
+    #[test]
+    fn slayer_helmet_context() {
+        let gear = player(&[("Slayer helmet (i)", EquipmentSlot::HEAD)]).gear;
+        let on_task = CombatContext::default();
+        let off_task = CombatContext {
+            on_task: false,
+            ..Default::default()
+        };
+        let raid = CombatContext {
+            raid: Raid::CHAMBERS,
+            ..Default::default()
+        };
+        assert_eq!(gear.regular_bonus(&on_task, &AttackType::RANGED), 1.15);
+        assert_eq!(gear.regular_bonus(&off_task, &AttackType::RANGED), 1.0);
+        assert_eq!(gear.regular_bonus(&raid, &AttackType::RANGED), 1.0);
+    }
+
+    #[test]
+    fn salve_over_slayer_helmet() {
+        let helm = ("Slayer helmet (i)", EquipmentSlot::HEAD);
+        let bonus = |neck: &str, attack_type: &AttackType| {
+            player(&[helm, (neck, EquipmentSlot::NECK)])
+                .gear
+                .undead_bonus(&CombatContext::default(), attack_type)
+        };
+        assert_eq!(bonus("Salve amulet(ei)", &AttackType::RANGED), 1.2);
+        assert_eq!(bonus("Salve amulet(ei)", &AttackType::MAGIC), 1.2);
+        assert_eq!(bonus("Salve amulet(i)", &AttackType::RANGED), 7.0 / 6.0);
+        assert_eq!(bonus("Salve amulet(i)", &AttackType::MAGIC), 1.15);
+        // The unimbued amulet does nothing for ranged, so the helmet applies instead
+        assert_eq!(bonus("Salve amulet (e)", &AttackType::RANGED), 1.15);
+        assert_eq!(bonus("Salve amulet (e)", &AttackType::SLASH), 1.2);
+    }
+
+    #[test]
+    fn black_mask() {
+        let gear = player(&[("Black mask", EquipmentSlot::HEAD)]).gear;
+        let on_task = CombatContext::default();
+        assert_eq!(gear.regular_bonus(&on_task, &AttackType::STAB), 7.0 / 6.0);
+        assert_eq!(gear.regular_bonus(&on_task, &AttackType::RANGED), 1.0);
+        assert_eq!(gear.regular_bonus(&on_task, &AttackType::MAGIC), 1.0);
+        let imbued = player(&[("Black mask (i)", EquipmentSlot::HEAD)]).gear;
+        assert_eq!(imbued.regular_bonus(&on_task, &AttackType::MAGIC), 1.15);
+    }
+
+    #[test]
+    fn karils_crossbow_ammo() {
+        let mut gear = player(&[("Bolt rack", EquipmentSlot::AMMO)]).gear;
+        gear.add_weapon(Some(weapon("Karil's crossbow")));
+        assert!(gear.has_required_ammo());
+        gear.add_equipment(
+            &EquipmentSlot::AMMO,
+            Some(item("Adamant bolts", EquipmentSlot::AMMO)),
+        );
+        assert!(!gear.has_required_ammo());
+        gear.add_weapon(Some(weapon("Rune crossbow")));
+        assert!(gear.has_required_ammo());
+    }
+
+    #[test]
+    fn monster_with_null_stats() {
+        let monster = fixtures::monster(serde_json::json!({
+            "hitpoints": null,
+            "max_hit": null,
+            "attack_speed": null,
+            "attack_type": null,
+        }));
+        assert_eq!(monster.hitpoints, 0);
+        assert_eq!(monster.max_hit(), 0);
+        assert_eq!(monster.attack_interval(), 2.4);
+        assert!(monster.attack_types().is_empty());
+    }
+
+    #[test]
+    fn tumekens_shadow_in_tombs() {
+        let mut gear = player(&[]).gear;
+        let mut cape = item("Test cape", EquipmentSlot::CAPE);
+        cape.equipment.attack_magic = 10;
+        gear.add_equipment(&EquipmentSlot::CAPE, Some(cape));
+        gear.add_weapon(Some(weapon("Tumeken's shadow")));
+        let tombs = CombatContext {
+            raid: Raid::TOMBS,
+            ..Default::default()
+        };
+        let bonus =
+            |context: &CombatContext| gear.attack_equipment_bonus(context, &AttackType::MAGIC);
+        assert_eq!(bonus(&CombatContext::default()), 30);
+        assert_eq!(bonus(&tombs), 40);
+    }
+
+    fn armed(items: &[(&str, EquipmentSlot)], name: &str) -> Player {
+        let mut player = player(items);
+        player.gear.add_weapon(Some(weapon(name)));
+        player
+    }
+
+    #[test]
+    fn dharok_scaling() {
+        let mut player = armed(
+            &[
+                ("Dharok's helm", EquipmentSlot::HEAD),
+                ("Dharok's platebody", EquipmentSlot::BODY),
+                ("Dharok's platelegs", EquipmentSlot::LEGS),
+            ],
+            "Dharok's greataxe",
+        );
+        let target = fixtures::monster(serde_json::json!({}));
+        let mut max_hit = |current| {
+            player.levels.hitpoints = Level { base: 99, current };
+            player.max_hit(&target, &AttackStyle::AGGRESSIVE, &AttackType::SLASH)
+        };
+        assert_eq!(max_hit(99), 11);
+        assert_eq!(max_hit(50), 16);
+        assert_eq!(max_hit(1), 21);
+    }
+
+    #[test]
+    fn inquisitor_pieces() {
+        let helm = ("Inquisitor's great helm", EquipmentSlot::HEAD);
+        let full = [
+            helm,
+            ("Inquisitor's hauberk", EquipmentSlot::BODY),
+            ("Inquisitor's plateskirt", EquipmentSlot::LEGS),
+        ];
+        let bonus = |player: Player| player.gear.set_damage_bonus(&AttackType::CRUSH);
+        assert!((bonus(player(&[helm])) - 1.005).abs() < 1e-9);
+        assert!((bonus(player(&full)) - 1.025).abs() < 1e-9);
+        assert!((bonus(armed(&full[..2], "Inquisitor's mace")) - 1.05).abs() < 1e-9);
+        assert!((bonus(armed(&full, "Inquisitor's mace")) - 1.075).abs() < 1e-9);
+        assert_eq!(player(&full).gear.set_damage_bonus(&AttackType::SLASH), 1.0);
+    }
+
+    #[test]
+    fn crystal_pieces() {
+        let pieces = [
+            ("Crystal helm", EquipmentSlot::HEAD),
+            ("Crystal body", EquipmentSlot::BODY),
+            ("Crystal legs", EquipmentSlot::LEGS),
+        ];
+        let bonus = |player: Player| {
+            (
+                player.gear.set_accuracy_bonus(&AttackType::RANGED),
+                player.gear.set_damage_bonus(&AttackType::RANGED),
+            )
+        };
+        let (accuracy, damage) = bonus(armed(&pieces[..2], "Bow of faerdhinen"));
+        assert!((accuracy - 1.2).abs() < 1e-9 && (damage - 1.1).abs() < 1e-9);
+        let (accuracy, damage) = bonus(armed(&pieces, "Bow of faerdhinen"));
+        assert!((accuracy - 1.3).abs() < 1e-9 && (damage - 1.15).abs() < 1e-9);
+        assert_eq!(bonus(armed(&pieces, "Twisted bow")), (1.0, 1.0));
+    }
+
+    #[test]
+    fn tzhaar_weapon_sets() {
+        let obsidian = [
+            ("Obsidian helmet", EquipmentSlot::HEAD),
+            ("Obsidian platebody", EquipmentSlot::BODY),
+            ("Obsidian platelegs", EquipmentSlot::LEGS),
+        ];
+        let necklace = [("Berserker necklace", EquipmentSlot::NECK)];
+        let both = [obsidian.as_slice(), necklace.as_slice()].concat();
+        let bonus = |player: Player| {
+            (
+                player.gear.set_accuracy_bonus(&AttackType::CRUSH),
+                player.gear.set_damage_bonus(&AttackType::CRUSH),
+            )
+        };
+        assert_eq!(bonus(armed(&obsidian, "Tzhaar-ket-om")), (1.1, 1.1));
+        assert_eq!(bonus(armed(&necklace, "Toktz-xil-ak")), (1.0, 1.2));
+        let (accuracy, damage) = bonus(armed(&both, "Tzhaar-ket-em"));
+        assert!(accuracy == 1.1 && (damage - 1.32).abs() < 1e-9);
+        assert_eq!(bonus(armed(&both, "Abyssal whip")), (1.0, 1.0));
+        let ranged = armed(&both, "Toktz-xil-ul");
+        assert_eq!(ranged.gear.set_damage_bonus(&AttackType::RANGED), 1.0);
+    }
+
+    #[test]
+    fn karil_second_hit() {
+        let player = armed(
+            &[
+                ("Karil's coif", EquipmentSlot::HEAD),
+                ("Karil's leathertop", EquipmentSlot::BODY),
+                ("Karil's leatherskirt", EquipmentSlot::LEGS),
+                ("Amulet of the damned", EquipmentSlot::NECK),
+                ("Bolt rack", EquipmentSlot::AMMO),
+            ],
+            "Karil's crossbow",
+        );
+        let target = fixtures::monster(serde_json::json!({}));
+        let style = (AttackStyle::ACCURATE, AttackType::RANGED);
+        let accuracy = player.hit_chance(&target, &style);
+        let max_hit = player.max_hit(&target, &style.0, &style.1);
+        let hit = player.hit_distribution(&target, &style);
+        // The top damage needs both rolls to land on their max
+        let top = 0.25 * accuracy / (max_hit + 1) as f64 * accuracy / (max_hit / 2 + 1) as f64;
+        assert_eq!(hit.max_hit(), max_hit + max_hit / 2);
+        assert!((hit.probability(hit.max_hit()) - top).abs() < 1e-12);
+        let expected =
+            accuracy * max_hit as f64 / 2.0 + 0.25 * accuracy * (max_hit / 2) as f64 / 2.0;
+        assert!((hit.expected() - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn trip_average() {
+        let boosts = Boosts {
+            strength_potion: StrengthPotion::SUPERSTRENGTH,
+            ..Default::default()
+        };
+        let player = player(&[]).with_boosts(&boosts);
+        let target = fixtures::monster(serde_json::json!({}));
+        let style = (AttackStyle::AGGRESSIVE, AttackType::CRUSH);
+        let trip = |minutes, redose_minutes| Trip {
+            minutes,
+            redose_minutes,
+        };
+        let after = |minutes| {
+            let mut player = player.clone();
+            player.set_boost_minutes(minutes);
+            player.dps(&target, &style)
+        };
+        let fresh = player.dps(&target, &style);
+        // Drinking every minute keeps the full boost
+        let redosed = player.trip_dps(&target, &style, &trip(60, Some(1)));
+        assert!((redosed - fresh).abs() < 1e-12);
+        let once = player.trip_dps(&target, &style, &trip(20, None));
+        let expected = (0..20).map(after).sum::<f64>() / 20.0;
+        assert!((once - expected).abs() < 1e-12);
+        assert!(once < fresh);
+        let redosed = player.trip_dps(&target, &style, &trip(20, Some(2)));
+        assert!((redosed - (after(0) + after(1)) / 2.0).abs() < 1e-12);
+        assert!(once < redosed);
+    }
+
+    fn drained(monster: &Monster, effects: &[(SpecialEffect, isize)]) -> isize {
+        let mut monster = monster.clone();
+        for (effect, damage) in effects {
+            monster.drain_defence(effect, *damage);
+        }
+        monster.defence_level()
+    }
+
+    #[test]
+    fn defence_drains() {
+        let monster = fixtures::monster(serde_json::json!({"defence_level": 200}));
+        let demon = fixtures::monster(serde_json::json!({
+            "defence_level": 200,
+            "attributes": ["demon"],
+        }));
+        let warhammer = SpecialEffect::DRAINPERCENT(30);
+        let arclight = SpecialEffect::DRAINBASEPERCENT(5);
+        // Dragon warhammer drains 30% of the current level, but only when it hits
+        assert_eq!(drained(&monster, &[(warhammer, 40)]), 140);
+        assert_eq!(drained(&monster, &[(warhammer, 40), (warhammer, 40)]), 98);
+        assert_eq!(drained(&monster, &[(warhammer, 0)]), 200);
+        // Arclight drains 5% of the base level, twice that from demons
+        assert_eq!(drained(&monster, &[(arclight, 10), (arclight, 10)]), 180);
+        assert_eq!(drained(&demon, &[(arclight, 10)]), 180);
+        // Bandos godsword drains the damage dealt
+        assert_eq!(drained(&monster, &[(SpecialEffect::DRAINDAMAGE, 55)]), 145);
+    }
+
+    #[test]
+    fn defence_floors() {
+        let akkha = fixtures::monster(serde_json::json!({"name": "Akkha", "defence_level": 80}));
+        assert_eq!(drained(&akkha, &[(SpecialEffect::DRAINDAMAGE, 50)]), 70);
+        assert_eq!(drained(&akkha, &[(SpecialEffect::DRAINPERCENT(30), 1)]), 70);
+        let other = fixtures::monster(serde_json::json!({"defence_level": 80}));
+        assert_eq!(drained(&other, &[(SpecialEffect::DRAINDAMAGE, 100)]), 0);
+    }
 }
-*/
@@ -0,0 +1,273 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::boosts::Boosts;
+use crate::engine::TICKS_PER_MINUTE;
+use crate::levels::{Level, Levels};
+use crate::player::{AttackPrayer, AttackType, MagicPrayer, RangedPrayer, StrengthPrayer};
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ProtectionPrayer {
+    #[default]
+    NONE,
+    MELEE,
+    MISSILES,
+    MAGIC,
+}
+
+pub const PROTECTION_PRAYERS: [ProtectionPrayer; 4] = [
+    ProtectionPrayer::NONE,
+    ProtectionPrayer::MELEE,
+    ProtectionPrayer::MISSILES,
+    ProtectionPrayer::MAGIC,
+];
+
+impl fmt::Display for ProtectionPrayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ProtectionPrayer::NONE => "no protection",
+                ProtectionPrayer::MELEE => "protect from melee",
+                ProtectionPrayer::MISSILES => "protect from missiles",
+                ProtectionPrayer::MAGIC => "protect from magic",
+            }
+        )
+    }
+}
+
+impl ProtectionPrayer {
+    pub fn unlocked(&self, levels: &Levels) -> bool {
+        let required = match self {
+            ProtectionPrayer::NONE => 1,
+            ProtectionPrayer::MELEE => 43,
+            ProtectionPrayer::MISSILES => 40,
+            ProtectionPrayer::MAGIC => 37,
+        };
+        levels.prayer.base >= required
+    }
+
+    pub fn protects(&self, attack_type: &AttackType) -> bool {
+        matches!(
+            (self, attack_type),
+            (
+                ProtectionPrayer::MELEE,
+                AttackType::STAB | AttackType::SLASH | AttackType::CRUSH
+            ) | (ProtectionPrayer::MISSILES, AttackType::RANGED)
+                | (
+                    ProtectionPrayer::MAGIC,
+                    AttackType::MAGIC | AttackType::SPELLCASTING | AttackType::DEFENSIVECASTING
+                )
+        )
+    }
+
+    pub fn drain_effect(&self) -> isize {
+        match self {
+            ProtectionPrayer::NONE => 0,
+            _ => 12,
+        }
+    }
+}
+
+impl AttackPrayer {
+    pub fn drain_effect(&self) -> isize {
+        match self {
+            AttackPrayer::NONE => 0,
+            AttackPrayer::CLARITY => 1,
+            AttackPrayer::IMPROVED => 6,
+            AttackPrayer::INCREDIBLE => 12,
+            AttackPrayer::CHIVALRY | AttackPrayer::PIETY => 24,
+        }
+    }
+}
+
+impl StrengthPrayer {
+    pub fn drain_effect(&self) -> isize {
+        match self {
+            StrengthPrayer::NONE => 0,
+            StrengthPrayer::BURST => 1,
+            StrengthPrayer::SUPERHUMAN => 6,
+            StrengthPrayer::ULTIMATE => 12,
+            StrengthPrayer::CHIVALRY | StrengthPrayer::PIETY => 24,
+        }
+    }
+}
+
+impl RangedPrayer {
+    pub fn drain_effect(&self) -> isize {
+        match self {
+            RangedPrayer::NONE => 0,
+            RangedPrayer::SHARPEYE => 1,
+            RangedPrayer::HAWKEYE => 6,
+            RangedPrayer::EAGLEEYE => 12,
+            RangedPrayer::RIGOUR => 24,
+        }
+    }
+}
+
+impl MagicPrayer {
+    pub fn drain_effect(&self) -> isize {
+        match self {
+            MagicPrayer::NONE => 0,
+            MagicPrayer::MYSTICWILL => 1,
+            MagicPrayer::MYSTICLORE => 6,
+            MagicPrayer::MYSTICMIGHT => 12,
+            MagicPrayer::AUGURY => 24,
+        }
+    }
+}
+
+/// Summed drain effect of every active offensive prayer. Chivalry and Piety fill both the
+/// attack and strength choice but are a single prayer, so they only count once.
+pub fn drain_effect(boosts: &Boosts) -> isize {
+    let strength = match (&boosts.attack_prayer, &boosts.strength_prayer) {
+        (AttackPrayer::CHIVALRY, StrengthPrayer::CHIVALRY)
+        | (AttackPrayer::PIETY, StrengthPrayer::PIETY) => 0,
+        (_, strength) => strength.drain_effect(),
+    };
+    boosts.attack_prayer.drain_effect()
+        + strength
+        + boosts.ranged_prayer.drain_effect()
+        + boosts.magic_prayer.drain_effect()
+}
+
+/// Prayer points drained per minute. Every tick the drain effects add up, and a point is
+/// lost whenever they exceed the resistance of 60 plus twice the prayer bonus.
+pub fn drain_per_minute(drain_effect: isize, prayer_bonus: isize) -> f64 {
+    let resistance = (60 + 2 * prayer_bonus).max(1) as f64;
+    drain_effect as f64 * TICKS_PER_MINUTE as f64 / resistance
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Food {
+    LOBSTER,
+    SWORDFISH,
+    MONKFISH,
+    KARAMBWAN,
+    SHARK,
+    MANTARAY,
+    DARKCRAB,
+    ANGLERFISH,
+    /// A single dose.
+    SARADOMINBREW,
+}
+
+impl Food {
+    /// Hitpoints healed by one bite, where anglerfish and brews scale with the hitpoints level.
+    pub fn heal(&self, hitpoints: &Level) -> isize {
+        match self {
+            Food::LOBSTER => 12,
+            Food::SWORDFISH => 14,
+            Food::MONKFISH => 16,
+            Food::KARAMBWAN => 18,
+            Food::SHARK => 20,
+            Food::MANTARAY | Food::DARKCRAB => 22,
+            Food::ANGLERFISH => {
+                let bonus = match hitpoints.base {
+                    ..=24 => 2,
+                    25..=49 => 4,
+                    50..=74 => 6,
+                    75..=92 => 8,
+                    _ => 13,
+                };
+                hitpoints.base / 10 + bonus
+            }
+            Food::SARADOMINBREW => hitpoints.base * 15 / 100 + 2,
+        }
+    }
+}
+
+/// Supplies brought along for a trip.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct Inventory {
+    /// Doses of prayer potion.
+    pub prayer_potions: isize,
+    /// Doses of super restore.
+    pub super_restores: isize,
+    /// Number of each food.
+    pub food: HashMap<Food, isize>,
+}
+
+impl Inventory {
+    /// Prayer points available over a trip: the points the player starts with plus every
+    /// dose, which restores a quarter of the base prayer level on top of a flat amount.
+    pub fn prayer_points(&self, prayer: &Level) -> isize {
+        let prayer_potion = 7 + prayer.base / 4;
+        let super_restore = 8 + prayer.base / 4;
+        prayer.current + self.prayer_potions * prayer_potion + self.super_restores * super_restore
+    }
+
+    /// Hitpoints available over a trip, starting from the current hitpoints and eating all
+    /// the food without any of it going to waste.
+    pub fn hitpoints(&self, hitpoints: &Level) -> isize {
+        let food: isize = self
+            .food
+            .iter()
+            .map(|(food, count)| food.heal(hitpoints) * count)
+            .sum();
+        hitpoints.current + food
+    }
+}
+
+/// Minutes the prayer points last at the given drain rate.
+pub fn prayer_minutes(prayer_points: isize, drain_per_minute: f64) -> f64 {
+    if drain_per_minute <= 0.0 {
+        f64::INFINITY
+    } else {
+        prayer_points as f64 / drain_per_minute
+    }
+}
+
+/// Minutes the hitpoints last while taking `damage_per_second`.
+pub fn survival_minutes(hitpoints: isize, damage_per_second: f64) -> f64 {
+    if damage_per_second <= 0.0 {
+        f64::INFINITY
+    } else {
+        hitpoints as f64 / damage_per_second / 60.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn piety_drain() {
+        let piety = Boosts {
+            attack_prayer: AttackPrayer::PIETY,
+            strength_prayer: StrengthPrayer::PIETY,
+            ..Default::default()
+        };
+        let drain = drain_effect(&piety);
+        assert_eq!(drain, 24);
+        assert_eq!(drain_per_minute(drain, 0), 40.0);
+        // Thirty prayer bonus doubles the resistance and halves the drain
+        assert_eq!(drain_per_minute(drain, 30), 20.0);
+        assert_eq!(prayer_minutes(99, drain_per_minute(drain, 30)), 4.95);
+    }
+
+    #[test]
+    fn restore_doses() {
+        let inventory = Inventory {
+            prayer_potions: 4,
+            super_restores: 8,
+            ..Default::default()
+        };
+        // Doses restore 31 and 32 points at 99 prayer
+        assert_eq!(
+            inventory.prayer_points(&Level::new(99)),
+            99 + 4 * 31 + 8 * 32
+        );
+        let drained = Level {
+            base: 70,
+            current: 10,
+        };
+        assert_eq!(inventory.prayer_points(&drained), 10 + 4 * 24 + 8 * 25);
+        assert_eq!(Inventory::default().prayer_points(&drained), 10);
+    }
+}
@@ -0,0 +1,385 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::context::CombatContext;
+use crate::player::Monster;
+
+/// A Chambers of Xeric party, which decides how much tougher every monster in the raid is.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct ChambersParty {
+    pub size: isize,
+    /// Combat level of the highest levelled player in the party.
+    pub max_combat_level: isize,
+    pub challenge_mode: bool,
+}
+
+impl Default for ChambersParty {
+    fn default() -> Self {
+        ChambersParty {
+            size: 1,
+            max_combat_level: 126,
+            challenge_mode: false,
+        }
+    }
+}
+
+impl ChambersParty {
+    /// Every stat first scales with the highest combat level in the party.
+    fn combat_scaled(&self, level: isize) -> isize {
+        level * self.max_combat_level.clamp(3, 126) / 126
+    }
+
+    fn challenge_scaled(&self, level: isize) -> isize {
+        if self.challenge_mode {
+            level * 3 / 2
+        } else {
+            level
+        }
+    }
+
+    fn extra_players(&self) -> isize {
+        (self.size - 1).max(0)
+    }
+
+    /// Each pair of players adds the base hitpoints once more.
+    fn hitpoints(&self, hitpoints: isize) -> isize {
+        let party = self.combat_scaled(hitpoints) * (1 + self.size.max(1) / 2);
+        self.challenge_scaled(party)
+    }
+
+    /// Defence grows by 0.7% per extra player, plus the square root of the extra players.
+    fn defence(&self, level: isize) -> isize {
+        let extra = self.extra_players();
+        let percent = 100 + (extra as f64).sqrt().floor() as isize + extra * 7 / 10;
+        self.challenge_scaled(self.combat_scaled(level) * percent / 100)
+    }
+
+    /// Offensive levels grow by 1% per extra player, plus 7% per whole square root.
+    fn offence(&self, level: isize) -> isize {
+        let extra = self.extra_players();
+        let percent = 100 + (extra as f64).sqrt().floor() as isize * 7 + extra;
+        self.challenge_scaled(self.combat_scaled(level) * percent / 100)
+    }
+}
+
+/// The monster as it spawns in a Chambers of Xeric raid for `party`. The monsters in the
+/// store hold the stats of a solo raid at combat level 126.
+pub fn scale_chambers(monster: &Monster, party: &ChambersParty) -> Monster {
+    monster.rescaled(
+        |x| party.hitpoints(x),
+        |x| party.defence(x),
+        |x| party.offence(x),
+        |x| party.offence(x),
+    )
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum TombsPath {
+    CRONDIS,
+    SCABARAS,
+    HET,
+    APMEKEN,
+}
+
+/// Path bosses, whose hitpoints also scale with the level of their path.
+const PATH_BOSSES: &[(&str, TombsPath)] = &[
+    ("Zebak", TombsPath::CRONDIS),
+    ("Kephri", TombsPath::SCABARAS),
+    ("Akkha", TombsPath::HET),
+    ("Ba-Ba", TombsPath::APMEKEN),
+];
+
+/// What an invocation does to a Tombs of Amascut raid: how much it adds to the raid level
+/// and how much it raises monster defence and accuracy, in percent.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct Invocation {
+    pub raid_level: isize,
+    pub defence: isize,
+    pub accuracy: isize,
+}
+
+const fn invocation(raid_level: isize) -> Invocation {
+    Invocation {
+        raid_level,
+        defence: 0,
+        accuracy: 0,
+    }
+}
+
+/// Invocations by name. Their effects on monster defence and accuracy aren't tabled yet, so
+/// presets give invocations with such effects in full.
+const INVOCATIONS: &[(&str, Invocation)] = &[
+    ("Try Again", invocation(5)),
+    ("Persistence", invocation(10)),
+    ("Softcore Run", invocation(15)),
+    ("Hardcore Run", invocation(25)),
+    ("Walk for It", invocation(10)),
+    ("Jog for It", invocation(15)),
+    ("Run for It", invocation(20)),
+    ("Sprint for It", invocation(25)),
+    ("Need Some Help?", invocation(15)),
+    ("Need Less Help?", invocation(25)),
+    ("No Help Needed", invocation(40)),
+    ("Walk the Path", invocation(50)),
+    ("Pathseeker", invocation(15)),
+    ("Pathfinder", invocation(40)),
+    ("Pathmaster", invocation(50)),
+    ("Quiet Prayers", invocation(20)),
+    ("Deadly Prayers", invocation(20)),
+    ("On a Diet", invocation(15)),
+    ("Dehydration", invocation(30)),
+    ("Overly Draining", invocation(15)),
+    ("Upset Stomach", invocation(15)),
+    ("Double Trouble", invocation(20)),
+    ("Keep Back", invocation(10)),
+    ("Stay Vigilant", invocation(15)),
+    ("Feeling Special?", invocation(20)),
+    ("Mind the Gap!", invocation(10)),
+    ("Gotta Have Faith", invocation(10)),
+    ("Jungle Japes", invocation(5)),
+    ("Shaking Things Up", invocation(10)),
+    ("Boulderdash", invocation(10)),
+    ("Ancient Haste", invocation(10)),
+    ("Acceleration", invocation(10)),
+    ("Penetration", invocation(10)),
+    ("Overclocked", invocation(10)),
+    ("Overclocked 2", invocation(10)),
+    ("Insanity", invocation(50)),
+    ("Blowing Mud", invocation(10)),
+    ("Medic!", invocation(15)),
+    ("Aerial Assault", invocation(10)),
+    ("Not Just a Head", invocation(15)),
+    ("Arterial Spray", invocation(10)),
+    ("Blood Thinners", invocation(5)),
+];
+
+impl Invocation {
+    pub fn from_name(name: &str) -> Option<Invocation> {
+        INVOCATIONS.iter().find(|x| x.0 == name).map(|x| x.1)
+    }
+}
+
+/// An invocation in a preset, either picked by name from the table or given in full.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum PresetInvocation {
+    NAMED(String),
+    CUSTOM(Invocation),
+}
+
+impl PresetInvocation {
+    fn invocation(&self) -> Option<Invocation> {
+        match self {
+            PresetInvocation::NAMED(name) => Invocation::from_name(name),
+            PresetInvocation::CUSTOM(invocation) => Some(*invocation),
+        }
+    }
+}
+
+/// A Tombs of Amascut raid, which decides how much tougher every monster in it is.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct TombsRaid {
+    /// Raid level, or the sum of the invocations when left out.
+    pub raid_level: Option<isize>,
+    pub party_size: isize,
+    /// Level of every path, where paths that aren't listed are at level 0.
+    pub path_levels: HashMap<TombsPath, isize>,
+    pub invocations: Vec<PresetInvocation>,
+}
+
+impl Default for TombsRaid {
+    fn default() -> Self {
+        TombsRaid {
+            raid_level: None,
+            party_size: 1,
+            path_levels: HashMap::new(),
+            invocations: Vec::new(),
+        }
+    }
+}
+
+impl TombsRaid {
+    /// Invocation names in the preset that don't match any invocation.
+    pub fn unknown_invocations(&self) -> Vec<&str> {
+        self.invocations
+            .iter()
+            .filter_map(|x| match x {
+                PresetInvocation::NAMED(name) if Invocation::from_name(name).is_none() => {
+                    Some(name.as_str())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn invocation_sum(&self, stat: impl Fn(&Invocation) -> isize) -> isize {
+        self.invocations
+            .iter()
+            .filter_map(|x| x.invocation())
+            .map(|x| stat(&x))
+            .sum()
+    }
+
+    pub fn raid_level(&self) -> isize {
+        self.raid_level
+            .unwrap_or_else(|| self.invocation_sum(|x| x.raid_level))
+    }
+
+    /// The first path level adds 8% hitpoints to the path boss and every further level 5%.
+    fn path_permille(&self, monster: &str) -> isize {
+        let level = PATH_BOSSES
+            .iter()
+            .find(|x| monster == x.0)
+            .and_then(|x| self.path_levels.get(&x.1))
+            .map_or(0, |x| (*x).max(0));
+        match level {
+            0 => 1000,
+            level => 1080 + 50 * (level - 1),
+        }
+    }
+
+    /// The second and third players add 90% hitpoints each, and every player after 60%.
+    fn party_permille(&self) -> isize {
+        let extra = (self.party_size - 1).clamp(0, 7);
+        1000 + 900 * extra.min(2) + 600 * (extra - 2).max(0)
+    }
+
+    fn hitpoints(&self, raid_level: isize, monster: &str, hitpoints: isize) -> isize {
+        let raid_level = hitpoints * raid_level_permille(raid_level) / 1000;
+        let path = raid_level * self.path_permille(monster) / 1000;
+        path * self.party_permille() / 1000
+    }
+}
+
+/// Hitpoints grow by 0.4% per raid level.
+fn raid_level_permille(raid_level: isize) -> isize {
+    1000 + 4 * raid_level
+}
+
+/// The monster as it spawns in a Tombs of Amascut raid at the raid level of `context`. The
+/// monsters in the store hold the stats of a solo raid at raid level 0.
+pub fn scale_tombs(monster: &Monster, raid: &TombsRaid, context: &CombatContext) -> Monster {
+    let defence = 100 + raid.invocation_sum(|x| x.defence);
+    let accuracy = 100 + raid.invocation_sum(|x| x.accuracy);
+    monster.rescaled(
+        |x| raid.hitpoints(context.raid_level, &monster.name, x),
+        |x| x * defence / 100,
+        |x| x * accuracy / 100,
+        |x| x,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::defender::Defender;
+    use crate::fixtures::monster;
+    use crate::player::AttackType;
+    use serde_json::json;
+
+    fn party(size: isize, challenge_mode: bool) -> ChambersParty {
+        ChambersParty {
+            size,
+            challenge_mode,
+            ..Default::default()
+        }
+    }
+
+    /// Tekton in a solo raid has 300 hitpoints, 205 defence and 390 attack.
+    fn tekton(party: &ChambersParty) -> (isize, isize, isize) {
+        (party.hitpoints(300), party.defence(205), party.offence(390))
+    }
+
+    #[test]
+    fn chambers_party_sizes() {
+        assert_eq!(tekton(&party(1, false)), (300, 205, 390));
+        assert_eq!(tekton(&party(2, false)), (600, 207, 421));
+        assert_eq!(tekton(&party(3, false)), (600, 209, 425));
+        assert_eq!(tekton(&party(5, false)), (900, 213, 460));
+    }
+
+    #[test]
+    fn chambers_challenge_mode() {
+        assert_eq!(tekton(&party(3, true)), (900, 313, 637));
+    }
+
+    #[test]
+    fn chambers_combat_level() {
+        let party = ChambersParty {
+            max_combat_level: 100,
+            ..Default::default()
+        };
+        assert_eq!(tekton(&party), (238, 162, 309));
+    }
+
+    fn tombs(raid_level: isize, path_level: isize, party_size: isize) -> TombsRaid {
+        TombsRaid {
+            raid_level: Some(raid_level),
+            party_size,
+            path_levels: HashMap::from([(TombsPath::SCABARAS, path_level)]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn tombs_raid_level() {
+        assert_eq!(raid_level_permille(0), 1000);
+        assert_eq!(raid_level_permille(300), 2200);
+        let raid = TombsRaid {
+            invocations: vec![
+                PresetInvocation::NAMED(String::from("Walk for It")),
+                PresetInvocation::NAMED(String::from("Insanity")),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(raid.raid_level(), 60);
+    }
+
+    #[test]
+    fn tombs_path_level() {
+        assert_eq!(tombs(0, 0, 1).path_permille("Kephri"), 1000);
+        assert_eq!(tombs(0, 1, 1).path_permille("Kephri"), 1080);
+        assert_eq!(tombs(0, 3, 1).path_permille("Kephri"), 1180);
+        assert_eq!(tombs(0, 3, 1).path_permille("Zebak"), 1000);
+    }
+
+    #[test]
+    fn tombs_party_size() {
+        assert_eq!(tombs(0, 0, 1).party_permille(), 1000);
+        assert_eq!(tombs(0, 0, 2).party_permille(), 1900);
+        assert_eq!(tombs(0, 0, 4).party_permille(), 3400);
+        assert_eq!(tombs(0, 0, 8).party_permille(), 5800);
+    }
+
+    /// Kephri has 80 hitpoints in a solo raid at raid level 0.
+    #[test]
+    fn tombs_kephri() {
+        let raid = tombs(300, 3, 4);
+        assert_eq!(raid.hitpoints(300, "Kephri", 80), 703);
+    }
+
+    #[test]
+    fn tombs_invocation_stats() {
+        let invocations: Vec<PresetInvocation> = serde_json::from_value(json!([
+            "Insanity",
+            {"raid_level": 10, "defence": 10, "accuracy": 20},
+        ]))
+        .unwrap();
+        // The raid level given outright doesn't hide what the invocations do to the stats
+        let raid = TombsRaid {
+            raid_level: Some(300),
+            invocations,
+            ..Default::default()
+        };
+        let base = monster(json!({"defence_level": 100, "attack_level": 100}));
+        let scaled = scale_tombs(&base, &raid, &CombatContext::default());
+        assert_eq!(base.max_defence_roll(&AttackType::STAB), 6976);
+        assert_eq!(scaled.max_defence_roll(&AttackType::STAB), 7616);
+        assert_eq!(base.max_attack_roll(&AttackType::STAB), 6976);
+        assert_eq!(scaled.max_attack_roll(&AttackType::STAB), 8256);
+    }
+}
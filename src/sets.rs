@@ -0,0 +1,215 @@
+use crate::player::{AttackType, EquipmentSlot};
+
+/// Bonuses that only apply when several matching pieces are worn together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SetEffect {
+    /// Boosts melee accuracy and strength.
+    VOIDMELEE,
+    /// Boosts ranged accuracy and strength.
+    VOIDRANGED,
+    /// Ranged void with the elite top and robe, which boosts ranged strength further.
+    ELITEVOIDRANGED,
+    /// Boosts magic accuracy.
+    VOIDMAGIC,
+    /// Magic void with the elite top and robe, which also adds magic damage.
+    ELITEVOIDMAGIC,
+    /// Melee damage grows with the hitpoints the player is missing.
+    DHAROK,
+    /// A quarter of attacks ignore defence and deal an extra point of damage.
+    VERAC,
+    /// Drains run energy, which does not affect damage.
+    TORAG,
+    /// A quarter of ranged attacks hit again for half damage.
+    KARIL,
+    /// A quarter of magic attacks deal 30% more damage.
+    AHRIM,
+    /// Boosts melee accuracy and damage with a Tzhaar weapon.
+    OBSIDIAN,
+    /// Boosts melee damage with a Tzhaar weapon.
+    BERSERKER,
+    /// Boosts crush accuracy and damage per piece, more so with the Inquisitor's mace.
+    INQUISITOR,
+    /// Boosts accuracy and damage of crystal bows per piece.
+    CRYSTAL,
+}
+
+/// Equipment slot and the name prefixes of items that fill it.
+type SetPiece = (EquipmentSlot, &'static [&'static str]);
+
+#[derive(Debug, Clone, Copy)]
+struct SetDefinition {
+    effect: SetEffect,
+    /// Pieces that all have to be worn for the effect to apply.
+    required: &'static [SetPiece],
+    /// Pieces that each add to the effect, at least one of which has to be worn.
+    pieces: &'static [SetPiece],
+}
+
+const fn set(
+    effect: SetEffect,
+    required: &'static [SetPiece],
+    pieces: &'static [SetPiece],
+) -> SetDefinition {
+    SetDefinition {
+        effect,
+        required,
+        pieces,
+    }
+}
+
+const VOID_BODY: SetPiece = (EquipmentSlot::BODY, &["Void knight top", "Elite void top"]);
+const VOID_LEGS: SetPiece = (
+    EquipmentSlot::LEGS,
+    &["Void knight robe", "Elite void robe"],
+);
+const ELITE_VOID_BODY: SetPiece = (EquipmentSlot::BODY, &["Elite void top"]);
+const ELITE_VOID_LEGS: SetPiece = (EquipmentSlot::LEGS, &["Elite void robe"]);
+const VOID_HANDS: SetPiece = (EquipmentSlot::HANDS, &["Void knight gloves"]);
+const DAMNED: SetPiece = (EquipmentSlot::NECK, &["Amulet of the damned"]);
+const TZHAAR_WEAPONS: SetPiece = (
+    EquipmentSlot::WEAPON,
+    &[
+        "Toktz-xil-ak",
+        "Toktz-xil-ek",
+        "Tzhaar-ket-em",
+        "Tzhaar-ket-om",
+    ],
+);
+
+#[rustfmt::skip]
+const SETS: &[SetDefinition] = &[
+    set(SetEffect::VOIDMELEE, &[(EquipmentSlot::HEAD, &["Void melee helm"]), VOID_BODY, VOID_LEGS, VOID_HANDS], &[]),
+    set(SetEffect::VOIDRANGED, &[(EquipmentSlot::HEAD, &["Void ranger helm"]), VOID_BODY, VOID_LEGS, VOID_HANDS], &[]),
+    set(SetEffect::ELITEVOIDRANGED, &[(EquipmentSlot::HEAD, &["Void ranger helm"]), ELITE_VOID_BODY, ELITE_VOID_LEGS, VOID_HANDS], &[]),
+    set(SetEffect::VOIDMAGIC, &[(EquipmentSlot::HEAD, &["Void mage helm"]), VOID_BODY, VOID_LEGS, VOID_HANDS], &[]),
+    set(SetEffect::ELITEVOIDMAGIC, &[(EquipmentSlot::HEAD, &["Void mage helm"]), ELITE_VOID_BODY, ELITE_VOID_LEGS, VOID_HANDS], &[]),
+    set(SetEffect::DHAROK, &[
+        (EquipmentSlot::HEAD, &["Dharok's helm"]),
+        (EquipmentSlot::BODY, &["Dharok's platebody"]),
+        (EquipmentSlot::LEGS, &["Dharok's platelegs"]),
+        (EquipmentSlot::WEAPON, &["Dharok's greataxe"]),
+    ], &[]),
+    set(SetEffect::VERAC, &[
+        (EquipmentSlot::HEAD, &["Verac's helm"]),
+        (EquipmentSlot::BODY, &["Verac's brassard"]),
+        (EquipmentSlot::LEGS, &["Verac's plateskirt"]),
+        (EquipmentSlot::WEAPON, &["Verac's flail"]),
+    ], &[]),
+    set(SetEffect::TORAG, &[
+        (EquipmentSlot::HEAD, &["Torag's helm"]),
+        (EquipmentSlot::BODY, &["Torag's platebody"]),
+        (EquipmentSlot::LEGS, &["Torag's platelegs"]),
+        (EquipmentSlot::WEAPON, &["Torag's hammers"]),
+    ], &[]),
+    set(SetEffect::KARIL, &[
+        (EquipmentSlot::HEAD, &["Karil's coif"]),
+        (EquipmentSlot::BODY, &["Karil's leathertop"]),
+        (EquipmentSlot::LEGS, &["Karil's leatherskirt"]),
+        (EquipmentSlot::WEAPON, &["Karil's crossbow"]),
+        DAMNED,
+    ], &[]),
+    set(SetEffect::AHRIM, &[
+        (EquipmentSlot::HEAD, &["Ahrim's hood"]),
+        (EquipmentSlot::BODY, &["Ahrim's robetop"]),
+        (EquipmentSlot::LEGS, &["Ahrim's robeskirt"]),
+        (EquipmentSlot::WEAPON, &["Ahrim's staff"]),
+        DAMNED,
+    ], &[]),
+    set(SetEffect::OBSIDIAN, &[
+        (EquipmentSlot::HEAD, &["Obsidian helmet"]),
+        (EquipmentSlot::BODY, &["Obsidian platebody"]),
+        (EquipmentSlot::LEGS, &["Obsidian platelegs"]),
+        TZHAAR_WEAPONS,
+    ], &[]),
+    set(SetEffect::BERSERKER, &[(EquipmentSlot::NECK, &["Berserker necklace"]), TZHAAR_WEAPONS], &[]),
+    set(SetEffect::INQUISITOR, &[], &[
+        (EquipmentSlot::HEAD, &["Inquisitor's great helm"]),
+        (EquipmentSlot::BODY, &["Inquisitor's hauberk"]),
+        (EquipmentSlot::LEGS, &["Inquisitor's plateskirt"]),
+    ]),
+    set(SetEffect::CRYSTAL, &[(EquipmentSlot::WEAPON, &["Bow of faerdhinen", "Crystal bow"])], &[
+        (EquipmentSlot::HEAD, &["Crystal helm"]),
+        (EquipmentSlot::BODY, &["Crystal body"]),
+        (EquipmentSlot::LEGS, &["Crystal legs"]),
+    ]),
+];
+
+/// A set effect the player is wearing, with the optional pieces that count towards it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveSet {
+    pub effect: SetEffect,
+    pub pieces: Vec<EquipmentSlot>,
+    /// Name of the item in the weapon slot, which some sets scale with.
+    weapon: String,
+}
+
+/// Finds every set effect given a lookup from slot to the name of the worn item.
+pub fn active_sets<'a>(worn: impl Fn(&EquipmentSlot) -> Option<&'a str>) -> Vec<ActiveSet> {
+    let wears = |piece: &SetPiece| {
+        worn(&piece.0).is_some_and(|name| piece.1.iter().any(|x| name.starts_with(x)))
+    };
+    SETS.iter()
+        .filter(|set| set.required.iter().all(wears))
+        .filter_map(|set| {
+            let pieces: Vec<EquipmentSlot> = set
+                .pieces
+                .iter()
+                .filter(|x| wears(x))
+                .map(|x| x.0)
+                .collect();
+            (set.pieces.is_empty() || !pieces.is_empty()).then(|| ActiveSet {
+                effect: set.effect,
+                pieces,
+                weapon: worn(&EquipmentSlot::WEAPON).unwrap_or_default().to_string(),
+            })
+        })
+        .collect()
+}
+
+impl ActiveSet {
+    /// Inquisitor's pieces give 0.5% each and 2.5% as a full set, which the mace
+    /// raises to 2.5% each and 7.5% as a full set.
+    fn inquisitor_bonus(&self) -> f64 {
+        let mace = self.weapon.starts_with("Inquisitor's mace");
+        match (self.pieces.len(), mace) {
+            (3, false) => 0.025,
+            (3, true) => 0.075,
+            (pieces, false) => pieces as f64 * 0.005,
+            (pieces, true) => pieces as f64 * 0.025,
+        }
+    }
+
+    /// Crystal armour bonuses as (accuracy, damage) for every worn piece.
+    fn crystal_bonus(&self) -> (f64, f64) {
+        self.pieces
+            .iter()
+            .map(|x| match x {
+                EquipmentSlot::HEAD => (0.05, 0.025),
+                EquipmentSlot::BODY => (0.15, 0.075),
+                EquipmentSlot::LEGS => (0.1, 0.05),
+                _ => (0.0, 0.0),
+            })
+            .fold((0.0, 0.0), |acc, x| (acc.0 + x.0, acc.1 + x.1))
+    }
+
+    /// Multiplier on the final attack roll.
+    pub fn accuracy_bonus(&self, attack_type: &AttackType) -> f64 {
+        match (self.effect, attack_type) {
+            (SetEffect::OBSIDIAN, AttackType::STAB | AttackType::SLASH | AttackType::CRUSH) => 1.1,
+            (SetEffect::INQUISITOR, AttackType::CRUSH) => 1.0 + self.inquisitor_bonus(),
+            (SetEffect::CRYSTAL, AttackType::RANGED) => 1.0 + self.crystal_bonus().0,
+            _ => 1.0,
+        }
+    }
+
+    /// Multiplier on the final max hit.
+    pub fn damage_bonus(&self, attack_type: &AttackType) -> f64 {
+        match (self.effect, attack_type) {
+            (SetEffect::OBSIDIAN, AttackType::STAB | AttackType::SLASH | AttackType::CRUSH) => 1.1,
+            (SetEffect::BERSERKER, AttackType::STAB | AttackType::SLASH | AttackType::CRUSH) => 1.2,
+            (SetEffect::INQUISITOR, AttackType::CRUSH) => 1.0 + self.inquisitor_bonus(),
+            (SetEffect::CRYSTAL, AttackType::RANGED) => 1.0 + self.crystal_bonus().1,
+            _ => 1.0,
+        }
+    }
+}
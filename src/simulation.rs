@@ -1,9 +1,16 @@
-use std::collections::HashSet;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt;
+use tracing::debug;
 
+use crate::boosts::{self, Boosts, Potion, Trip};
+use crate::defender::Defender;
 use crate::player::{
-    AttackStyle, AttackType, Equipment, EquipmentSlot, Gear, Monster, Player, SpareGear, Weapon,
+    AttackStyle, AttackType, CombatClass, Equipment, EquipmentSlot, Gear, Monster, Player,
+    SpareGear, Weapon,
 };
+use crate::prayer::{Inventory, ProtectionPrayer, PROTECTION_PRAYERS};
+use crate::special::SpecialAttack;
 
 // Interesting optimalization:
 // - ignore everything of gear that does not provide any attack bonuses
@@ -45,6 +52,7 @@ impl fmt::Display for GearSet {
 }
 
 impl GearSet {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         ammo: Option<Equipment>,
         body: Option<Equipment>,
@@ -59,21 +67,21 @@ impl GearSet {
         weapon: Option<Weapon>,
     ) -> Self {
         GearSet {
-            ammo: ammo,
-            body: body,
-            cape: cape,
-            feet: feet,
-            head: head,
-            legs: legs,
-            neck: neck,
-            ring: ring,
-            hands: hands,
-            shield: shield,
-            weapon: weapon,
+            ammo,
+            body,
+            cape,
+            feet,
+            head,
+            legs,
+            neck,
+            ring,
+            hands,
+            shield,
+            weapon,
         }
     }
 
-    fn equip_player(&self, base: &Player) -> Player {
+    pub fn equip_player(&self, base: &Player) -> Player {
         let mut p = base.clone();
         p.gear
             .add_equipment(&EquipmentSlot::AMMO, self.ammo.clone());
@@ -282,55 +290,446 @@ impl Simulation {
     }
 }
 
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Ranking {
+    #[default]
+    DPS,
+    TTK,
+    /// Damage dealt over a trip, which stops early once the prayer points or food run out.
+    TRIP,
+    /// Kills over a trip, which stops early the same way.
+    KILLS,
+}
+
+/// What the optimizer searches over and how it ranks the results.
+#[derive(Debug, Clone, Default)]
+pub struct RunConfig {
+    pub ranking: Ranking,
+    /// Potions in the bank, which makes every style pick its own prayers and potions.
+    pub potions: Option<Vec<Potion>>,
+    pub trip: Trip,
+    pub inventory: Inventory,
+    /// Combat classes the target can be attacked with, or every class when not given.
+    pub classes: Option<Vec<CombatClass>>,
+}
+
+impl RunConfig {
+    fn allows(&self, attack_type: &AttackType) -> bool {
+        self.classes
+            .as_ref()
+            .is_none_or(|x| x.contains(&attack_type.class()))
+    }
+
+    /// Minutes of the trip spent fighting, cut short when the prayers can't be kept up or
+    /// the food runs out.
+    fn fighting_minutes(
+        &self,
+        player: &Player,
+        target: &impl Defender,
+        style: &(AttackStyle, AttackType),
+    ) -> f64 {
+        player
+            .prayer_minutes(&self.inventory)
+            .min(player.survival_minutes(target, style, &self.inventory))
+            .min(self.trip.minutes as f64)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct StyleResult {
+    /// Damage per second at the peak of every boost.
+    pub dps: f64,
+    /// Damage per second averaged over the trip as potions wear off.
+    pub trip_dps: f64,
+    pub ttk: f64,
+    /// Minutes the prayers last with the restores in the inventory.
+    pub prayer_minutes: f64,
+    /// Minutes spent fighting before banking.
+    pub fighting_minutes: f64,
+    /// Damage dealt over the trip before banking.
+    pub trip_damage: f64,
+    /// Expected damage the monster deals to the player per second.
+    pub damage_taken: f64,
+    pub protection: ProtectionPrayer,
+    pub kills_per_trip: f64,
+    pub style: (AttackStyle, AttackType),
+    pub attack_bonus: isize,
+    pub strength_bonus: isize,
+    pub boosts: Boosts,
+}
+
+impl StyleResult {
+    /// Higher is better for every ranking, so time-to-kill is negated.
+    fn score(&self, ranking: Ranking) -> f64 {
+        match ranking {
+            Ranking::DPS => self.dps,
+            Ranking::TTK => -self.ttk,
+            Ranking::TRIP => self.trip_damage,
+            Ranking::KILLS => self.kills_per_trip,
+        }
+    }
+}
+
+/// The prayers and potions with the highest dps for `style`, out of the prayers unlocked by
+/// the player's levels and the potions in the bank. Picking by dps rather than by the
+/// requested ranking keeps the search cheap, and the two agree for any sensible boost.
+/// Ranking by trip or kills is the exception, as a hungrier prayer can end the trip sooner.
+pub fn run_boosts(
+    base: &Player,
+    target: &impl Defender,
+    style: &(AttackStyle, AttackType),
+    potions: &[Potion],
+    config: &RunConfig,
+) -> Boosts {
+    boosts::candidates(&base.levels, potions, &style.1, &base.context)
+        .into_iter()
+        .map(|x| {
+            let player = base.with_boosts(&x);
+            let dps = player.dps(target, style);
+            let score = match config.ranking {
+                Ranking::TRIP | Ranking::KILLS => {
+                    dps * config.fighting_minutes(&player, target, style)
+                }
+                _ => dps,
+            };
+            (score, x)
+        })
+        .max_by(|x, y| x.0.partial_cmp(&y.0).unwrap())
+        .map_or(base.boosts(), |x| x.1)
+}
+
+/// The protection prayer that keeps the player fighting the longest, which is none at all
+/// when praying only runs the prayer points out sooner.
+pub fn run_protection(
+    base: &Player,
+    target: &impl Defender,
+    style: &(AttackStyle, AttackType),
+    config: &RunConfig,
+) -> ProtectionPrayer {
+    PROTECTION_PRAYERS
+        .iter()
+        .filter(|x| x.unlocked(&base.levels))
+        .map(|x| {
+            let player = base.with_protection(*x);
+            (config.fighting_minutes(&player, target, style), *x)
+        })
+        .fold(
+            None,
+            |best: Option<(f64, ProtectionPrayer)>, x| match best {
+                Some(best) if best.0 >= x.0 => Some(best),
+                _ => Some(x),
+            },
+        )
+        .map_or(base.protection(), |x| x.1)
+}
+
+/// Best style of the wielded weapon. With potions in the config, every style also gets the
+/// best prayers and potions the player has, otherwise the player's own boosts are used.
 pub fn run_attack_styles(
     base: &Player,
-    monster: &Monster,
-) -> (f64, (AttackStyle, AttackType), isize, isize) {
-    let mut a: Vec<(f64, (AttackStyle, AttackType), isize, isize)> = base
+    target: &impl Defender,
+    config: &RunConfig,
+) -> Option<StyleResult> {
+    let ranking = config.ranking;
+    let mut a: Vec<StyleResult> = base
         .weapon_styles()
         .iter()
+        .filter(|x| config.allows(&x.1))
         .map(|x| {
-            (
-                base.dps(monster, true, x),
-                x.clone(),
-                base.gear.attack_equipment_bonus(&x.1),
-                base.gear.strength_equipment_bonus(&x.1),
-            )
+            let boosts = config.potions.as_ref().map_or(base.boosts(), |potions| {
+                run_boosts(base, target, x, potions, config)
+            });
+            let boosted = base.with_boosts(&boosts);
+            let protection = run_protection(&boosted, target, x, config);
+            let player = boosted.with_protection(protection);
+            let trip_dps = player.trip_dps(target, x, &config.trip);
+            let ttk = player.ttk(target, x);
+            let fighting_minutes = config.fighting_minutes(&player, target, x);
+            StyleResult {
+                dps: player.dps(target, x),
+                trip_dps,
+                ttk,
+                prayer_minutes: player.prayer_minutes(&config.inventory),
+                fighting_minutes,
+                trip_damage: trip_dps * 60.0 * fighting_minutes,
+                damage_taken: target.damage_per_second_to(&player, x),
+                protection,
+                kills_per_trip: fighting_minutes * 60.0 / ttk,
+                style: *x,
+                attack_bonus: player.gear.attack_equipment_bonus(&player.context, &x.1),
+                strength_bonus: player.gear.strength_equipment_bonus(&player.context, &x.1),
+                boosts,
+            }
         })
         .collect();
-    a.sort_unstable_by(|x, y| y.0.partial_cmp(&x.0).unwrap());
+    a.sort_unstable_by(|x, y| y.score(ranking).partial_cmp(&x.score(ranking)).unwrap());
 
-    a.first().unwrap().clone()
+    a.first().copied()
 }
 
+/// Best gear set and style, or none when no gear set has a weapon for the allowed styles.
 pub fn run(
     player: Player,
-    monster: &Monster,
-) -> ((f64, (AttackStyle, AttackType), isize, isize), GearSet) {
+    target: &impl Defender,
+    config: &RunConfig,
+) -> Option<(StyleResult, GearSet)> {
+    let ranking = config.ranking;
     let mut sim = Simulation::new(&player.gear, &player.equipment);
     sim.init();
     let gear = sim.get_gear_combinations();
 
-    let mut results: Vec<((f64, (AttackStyle, AttackType), isize, isize), &GearSet)> = gear
+    let mut results: Vec<(StyleResult, &GearSet)> = gear
         .iter()
-        .map(|x| (run_attack_styles(&x.equip_player(&player), monster), x))
+        .filter_map(|x| {
+            Some((
+                run_attack_styles(&x.equip_player(&player), target, config)?,
+                x,
+            ))
+        })
         .collect();
-    results.sort_unstable_by(|x, y| (y.0).0.partial_cmp(&(x.0).0).unwrap());
-    // Debug
+    results.sort_unstable_by(|x, y| y.0.score(ranking).partial_cmp(&x.0.score(ranking)).unwrap());
     for r in &results {
-        println!(
-            "{} (att: {}|str: {}) -> [{} - {}] {}",
-            (r.0).0,
-            (r.0).2,
-            (r.0).3,
-            ((r.0).1).0,
-            ((r.0).1).1,
+        debug!(
+            "{} dps ({:.3} over the trip), {:.1}s ttk, {:.1} min of prayer, {:.3} damage taken per second with {}, {:.1} kills per trip (att: {}|str: {}) -> [{} - {}] ({}) {}",
+            r.0.dps,
+            r.0.trip_dps,
+            r.0.ttk,
+            r.0.prayer_minutes,
+            r.0.damage_taken,
+            r.0.protection,
+            r.0.kills_per_trip,
+            r.0.attack_bonus,
+            r.0.strength_bonus,
+            r.0.style.0,
+            r.0.style.1,
+            r.0.boosts,
             r.1
         );
     }
 
-    let fst = results
-        .first()
-        .expect("This should not happen: we need to have at least one gearset..");
-    (fst.0, fst.1.clone())
+    results.first().map(|x| (x.0, x.1.clone()))
+}
+
+#[derive(Debug, Clone)]
+pub struct BoostContribution {
+    pub choice: String,
+    /// Damage per second lost by leaving out only this choice.
+    pub dps: f64,
+}
+
+/// How much each of the player's prayers and potions adds to the dps of `style`.
+pub fn boost_contributions(
+    player: &Player,
+    target: &impl Defender,
+    style: &(AttackStyle, AttackType),
+) -> Vec<BoostContribution> {
+    let dps = player.dps(target, style);
+    player
+        .boosts()
+        .choices()
+        .into_iter()
+        .map(|(choice, without)| BoostContribution {
+            choice,
+            dps: dps - player.with_boosts(&without).dps(target, style),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct SpecResult {
+    pub weapon: Weapon,
+    pub special: SpecialAttack,
+    pub style: (AttackStyle, AttackType),
+    pub expected_damage: f64,
+    /// Extra damage per second from spending regenerated energy on this weapon.
+    pub dps_gain: f64,
+}
+
+/// Picks the spec weapon to carry alongside the main weapon of `player`. Energy regenerates
+/// 10% every 30 seconds, so over a kill a special costing `energy` comes around every
+/// `3 * energy` seconds, each time taking the place of main weapon attacks for one attack
+/// of the spec weapon.
+pub fn run_special_attacks(
+    player: &Player,
+    monster: &Monster,
+    main: &StyleResult,
+) -> Option<SpecResult> {
+    let mut weapons = player.equipment.spare_weapons.clone();
+    weapons.extend(player.gear.weapon.clone());
+
+    let mut results: Vec<SpecResult> = weapons
+        .iter()
+        .filter_map(|weapon| {
+            let special = SpecialAttack::for_weapon(&weapon.name)?;
+            let spec_player = player.with_weapon(weapon);
+            spec_player
+                .weapon_styles()
+                .iter()
+                .filter_map(|style| {
+                    let damage = spec_player.special_distribution(monster, style)?.expected();
+                    let replaced = main.dps * spec_player.attack_interval(style);
+                    Some(SpecResult {
+                        weapon: weapon.clone(),
+                        special,
+                        style: *style,
+                        expected_damage: damage,
+                        dps_gain: (damage - replaced) / (3.0 * special.energy as f64),
+                    })
+                })
+                .max_by(|x, y| x.dps_gain.partial_cmp(&y.dps_gain).unwrap())
+        })
+        .filter(|x| x.dps_gain > 0.0)
+        .collect();
+    results.sort_unstable_by(|x, y| y.dps_gain.partial_cmp(&x.dps_gain).unwrap());
+
+    results.into_iter().next()
+}
+
+#[derive(Debug, Clone)]
+pub struct SpecPlan {
+    pub weapon: String,
+    pub specs: usize,
+    pub ttk: f64,
+    /// Time to kill with the main weapon alone.
+    pub baseline_ttk: f64,
+}
+
+/// Expected time to kill when opening with up to `specs` special attacks from the weapon of
+/// `spec_player` before switching to the main weapon of `player`. Every outcome of the
+/// specs is tracked as defence drained and damage dealt, so the main weapon's time to kill
+/// is computed against the drained monster with the hitpoints that are left.
+pub fn run_spec_plan(
+    player: &Player,
+    style: &(AttackStyle, AttackType),
+    spec_player: &Player,
+    spec_style: &(AttackStyle, AttackType),
+    monster: &Monster,
+    specs: usize,
+) -> Option<SpecPlan> {
+    let weapon = spec_player.gear.weapon.as_ref()?;
+    let special = SpecialAttack::for_weapon(&weapon.name)?;
+    let hitpoints = monster.hitpoints;
+
+    // (defence drained, damage dealt, specs used) -> probability, kept in order so that the
+    // probabilities are always summed the same way
+    let mut states: BTreeMap<(isize, isize, usize), f64> = BTreeMap::new();
+    states.insert((0, 0, 0), 1.0);
+    for _ in 0..specs {
+        let mut next = BTreeMap::new();
+        for ((drained, damage, used), p) in states {
+            if damage >= hitpoints {
+                *next.entry((drained, damage, used)).or_insert(0.0) += p;
+                continue;
+            }
+            let mut target = monster.clone();
+            target.set_defence_drained(drained);
+            let distribution = spec_player.special_distribution(&target, spec_style)?;
+            for (hit, q) in distribution.iter().filter(|x| x.1 > 0.0) {
+                let mut after = target.clone();
+                after.drain_defence(&special.effect, hit);
+                *next
+                    .entry((after.defence_drained(), damage + hit, used + 1))
+                    .or_insert(0.0) += p * q;
+            }
+        }
+        states = next;
+    }
+
+    let spec_interval = spec_player.attack_interval(spec_style);
+    let interval = player.attack_interval(style);
+    let mut attacks: HashMap<isize, Vec<f64>> = HashMap::new();
+    let ttk = states
+        .iter()
+        .map(|((drained, damage, used), p)| {
+            let remaining = attacks.entry(*drained).or_insert_with(|| {
+                let mut target = monster.clone();
+                target.set_defence_drained(*drained);
+                player
+                    .hit_distribution(&target, style)
+                    .expected_attacks_to_kill(hitpoints)
+            });
+            let main = remaining[(hitpoints - damage).max(0) as usize] * interval;
+            p * (*used as f64 * spec_interval + main)
+        })
+        .sum();
+
+    Some(SpecPlan {
+        weapon: weapon.name.clone(),
+        specs,
+        ttk,
+        baseline_ttk: player.ttk(monster, style),
+    })
+}
+
+/// Every opening of one to a full bar of special attacks from the spec weapons the player
+/// carries, fastest kill first.
+pub fn run_spec_plans(
+    player: &Player,
+    style: &(AttackStyle, AttackType),
+    monster: &Monster,
+) -> Vec<SpecPlan> {
+    let mut weapons = player.equipment.spare_weapons.clone();
+    weapons.extend(player.gear.weapon.clone());
+
+    let mut plans: Vec<SpecPlan> = weapons
+        .iter()
+        .filter_map(|weapon| {
+            let special = SpecialAttack::for_weapon(&weapon.name)?;
+            let spec_player = player.with_weapon(weapon);
+            let spec_style = spec_player.weapon_styles().into_iter().max_by(|x, y| {
+                let damage = |style| {
+                    spec_player
+                        .special_distribution(monster, style)
+                        .map_or(0.0, |x| x.expected())
+                };
+                damage(x).partial_cmp(&damage(y)).unwrap()
+            })?;
+            Some(
+                (1..=(100 / special.energy) as usize)
+                    .filter_map(|specs| {
+                        run_spec_plan(player, style, &spec_player, &spec_style, monster, specs)
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .flatten()
+        .collect();
+    plans.sort_unstable_by(|x, y| x.ttk.partial_cmp(&y.ttk).unwrap());
+
+    plans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use serde_json::json;
+
+    fn wielding(name: &str) -> Player {
+        fixtures::player().with_weapon(&Weapon {
+            name: String::from(name),
+            ..Weapon::default()
+        })
+    }
+
+    #[test]
+    fn warhammer_specs_before_scythe() {
+        let scythe = wielding("Scythe of vitur");
+        let warhammer = wielding("Dragon warhammer");
+        let style = (AttackStyle::AGGRESSIVE, AttackType::CRUSH);
+        let monster = fixtures::monster(json!({"hitpoints": 300, "defence_level": 250}));
+        let plan =
+            |specs| run_spec_plan(&scythe, &style, &warhammer, &style, &monster, specs).unwrap();
+
+        let none = plan(0);
+        assert_eq!(none.ttk, none.baseline_ttk);
+        assert_eq!(none.baseline_ttk, scythe.ttk(&monster, &style));
+
+        let one = plan(1);
+        let two = plan(2);
+        assert_eq!(two.weapon, "Dragon warhammer");
+        assert!(two.ttk < one.ttk);
+        assert!(one.ttk < none.ttk);
+    }
 }
@@ -0,0 +1,182 @@
+use std::fmt;
+
+use crate::distribution::HitDistribution;
+
+/// How a special attack turns its accuracy and max hit into damage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpecialHits {
+    SINGLE,
+    /// Two hits with independent accuracy rolls.
+    DOUBLE,
+    /// Two hits that either both land or both miss.
+    DOUBLELINKED,
+    /// Four cascading hits where later hits make up for earlier misses.
+    CLAWS,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpecialEffect {
+    NONE,
+    /// Lowers the target's defence by the damage dealt.
+    DRAINDAMAGE,
+    /// Lowers the target's defence by a percentage when the hit lands.
+    DRAINPERCENT(isize),
+    /// Lowers the target's defence by a percentage of its base level, twice that for demons.
+    DRAINBASEPERCENT(isize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpecialAttack {
+    pub weapon: &'static str,
+    pub energy: isize,
+    pub accuracy: f64,
+    pub damage: f64,
+    pub hits: SpecialHits,
+    pub effect: SpecialEffect,
+}
+
+const fn special(
+    weapon: &'static str,
+    energy: isize,
+    accuracy: f64,
+    damage: f64,
+    hits: SpecialHits,
+    effect: SpecialEffect,
+) -> SpecialAttack {
+    SpecialAttack {
+        weapon,
+        energy,
+        accuracy,
+        damage,
+        hits,
+        effect,
+    }
+}
+
+// Weapons are matched by prefix, so more specific names have to come first.
+#[rustfmt::skip]
+const SPECIAL_ATTACKS: &[SpecialAttack] = &[
+    special("Dragon dagger", 25, 1.15, 1.15, SpecialHits::DOUBLE, SpecialEffect::NONE),
+    special("Dragon claws", 50, 1.0, 1.0, SpecialHits::CLAWS, SpecialEffect::NONE),
+    special("Dragon longsword", 25, 1.0, 1.25, SpecialHits::SINGLE, SpecialEffect::NONE),
+    special("Dragon mace", 25, 1.25, 1.5, SpecialHits::SINGLE, SpecialEffect::NONE),
+    special("Dragon warhammer", 50, 1.0, 1.5, SpecialHits::SINGLE, SpecialEffect::DRAINPERCENT(30)),
+    special("Elder maul", 50, 1.25, 1.0, SpecialHits::SINGLE, SpecialEffect::DRAINPERCENT(35)),
+    special("Arclight", 50, 1.0, 1.0, SpecialHits::SINGLE, SpecialEffect::DRAINBASEPERCENT(5)),
+    special("Bandos godsword", 50, 2.0, 1.21, SpecialHits::SINGLE, SpecialEffect::DRAINDAMAGE),
+    special("Armadyl godsword", 50, 2.0, 1.375, SpecialHits::SINGLE, SpecialEffect::NONE),
+    special("Saradomin godsword", 50, 2.0, 1.1, SpecialHits::SINGLE, SpecialEffect::NONE),
+    special("Zamorak godsword", 50, 2.0, 1.1, SpecialHits::SINGLE, SpecialEffect::NONE),
+    special("Granite maul (or)", 50, 1.0, 1.0, SpecialHits::SINGLE, SpecialEffect::NONE),
+    special("Granite maul", 60, 1.0, 1.0, SpecialHits::SINGLE, SpecialEffect::NONE),
+    special("Abyssal dagger", 50, 1.25, 0.85, SpecialHits::DOUBLELINKED, SpecialEffect::NONE),
+    special("Toxic blowpipe", 50, 2.0, 1.5, SpecialHits::SINGLE, SpecialEffect::NONE),
+    special("Magic shortbow (i)", 50, 10.0 / 7.0, 1.0, SpecialHits::DOUBLE, SpecialEffect::NONE),
+    special("Magic shortbow", 55, 10.0 / 7.0, 1.0, SpecialHits::DOUBLE, SpecialEffect::NONE),
+    special("Armadyl crossbow", 40, 2.0, 1.0, SpecialHits::SINGLE, SpecialEffect::NONE),
+    special("Dragon crossbow", 60, 1.0, 1.2, SpecialHits::SINGLE, SpecialEffect::NONE),
+];
+
+impl fmt::Display for SpecialAttack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}%)", self.weapon, self.energy)
+    }
+}
+
+impl SpecialAttack {
+    pub fn for_weapon(name: &str) -> Option<SpecialAttack> {
+        SPECIAL_ATTACKS
+            .iter()
+            .find(|x| name.starts_with(x.weapon))
+            .copied()
+    }
+
+    /// Damage of one special attack given its (already multiplied) accuracy and max hit.
+    pub fn distribution(&self, accuracy: f64, max_hit: isize) -> HitDistribution {
+        let hit = HitDistribution::linear(accuracy, max_hit);
+        match self.hits {
+            SpecialHits::SINGLE => hit,
+            SpecialHits::DOUBLE => hit.combine(&hit),
+            SpecialHits::DOUBLELINKED => {
+                let landed = HitDistribution::linear(1.0, max_hit);
+                HitDistribution::miss().mix(&landed.combine(&landed), accuracy)
+            }
+            SpecialHits::CLAWS => claws_distribution(accuracy, max_hit),
+        }
+    }
+}
+
+/// Range of the hit that lands first and the total damage of all claw hits that follow from it.
+type ClawsCase = (isize, isize, fn(isize) -> isize);
+
+/// Dragon claws roll up to four accuracy checks, and the first one to land decides the
+/// range of the big hit and how the remaining hits follow from it.
+fn claws_distribution(accuracy: f64, max_hit: isize) -> HitDistribution {
+    let cases: [ClawsCase; 4] = [
+        (max_hit / 2, max_hit - 1, |x| {
+            x + x / 2 + x / 4 + (x / 4 + 1)
+        }),
+        (max_hit * 3 / 8, max_hit * 7 / 8, |x| {
+            x + x / 2 + (x / 2 + 1)
+        }),
+        (max_hit / 4, max_hit * 3 / 4, |x| x + (x + 1)),
+        (max_hit / 4, max_hit * 5 / 4, |x| x),
+    ];
+
+    let mut outcomes = Vec::new();
+    let mut all_missed = 1.0;
+    for (low, high, total) in cases.iter() {
+        let high = (*high).max(*low);
+        let chance = all_missed * accuracy / (high - low + 1) as f64;
+        outcomes.extend((*low..=high).map(|x| (total(x), chance)));
+        all_missed *= 1.0 - accuracy;
+    }
+    // Should every hit miss, the last two still deal one damage each two times out of three
+    outcomes.push((2, all_missed * 2.0 / 3.0));
+    outcomes.push((0, all_missed / 3.0));
+    HitDistribution::from_outcomes(outcomes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn claws_always_hitting() {
+        // The first hit lands between 20 and 39, and 20 turns into 20 + 10 + 5 + 6
+        let claws = claws_distribution(1.0, 40);
+        assert_close(claws.expected(), 59.0);
+        assert_close(claws.probability(41), 1.0 / 20.0);
+        assert_close(claws.probability(77), 1.0 / 20.0);
+        assert_eq!(claws.max_hit(), 77);
+        assert_close(claws.probability(0), 0.0);
+    }
+
+    #[test]
+    fn claws_half_accuracy() {
+        let claws = claws_distribution(0.5, 40);
+        assert_close(claws.expected(), 4133.0 / 84.0);
+        // All four rolls miss one time in sixteen
+        assert_close(claws.probability(0), 1.0 / 48.0);
+        assert_close(claws.probability(2), 1.0 / 24.0);
+    }
+
+    #[test]
+    fn abyssal_dagger_hits_land_together() {
+        let dagger = SpecialAttack::for_weapon("Abyssal dagger (p++)").unwrap();
+        let hits = dagger.distribution(0.5, 10);
+        assert_close(hits.expected(), 5.0);
+        assert_close(hits.probability(0), 0.5 + 0.5 / 121.0);
+        assert_close(hits.probability(1), 0.5 * 2.0 / 121.0);
+        assert_close(hits.probability(20), 0.5 / 121.0);
+    }
+}
@@ -0,0 +1,140 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Spellbook {
+    STANDARD,
+    ANCIENT,
+    ARCEUUS,
+}
+
+impl fmt::Display for Spellbook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Spellbook::STANDARD => "standard",
+                Spellbook::ANCIENT => "ancient",
+                Spellbook::ARCEUUS => "arceuus",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Spell {
+    pub name: &'static str,
+    pub spellbook: Spellbook,
+    base_max_hit: isize,
+}
+
+const fn spell(name: &'static str, spellbook: Spellbook, base_max_hit: isize) -> Spell {
+    Spell {
+        name,
+        spellbook,
+        base_max_hit,
+    }
+}
+
+const SPELLS: &[Spell] = &[
+    spell("Wind Strike", Spellbook::STANDARD, 2),
+    spell("Water Strike", Spellbook::STANDARD, 4),
+    spell("Earth Strike", Spellbook::STANDARD, 6),
+    spell("Fire Strike", Spellbook::STANDARD, 8),
+    spell("Wind Bolt", Spellbook::STANDARD, 9),
+    spell("Water Bolt", Spellbook::STANDARD, 10),
+    spell("Earth Bolt", Spellbook::STANDARD, 11),
+    spell("Fire Bolt", Spellbook::STANDARD, 12),
+    spell("Wind Blast", Spellbook::STANDARD, 13),
+    spell("Water Blast", Spellbook::STANDARD, 14),
+    spell("Earth Blast", Spellbook::STANDARD, 15),
+    spell("Fire Blast", Spellbook::STANDARD, 16),
+    spell("Wind Wave", Spellbook::STANDARD, 17),
+    spell("Water Wave", Spellbook::STANDARD, 18),
+    spell("Earth Wave", Spellbook::STANDARD, 19),
+    spell("Fire Wave", Spellbook::STANDARD, 20),
+    spell("Wind Surge", Spellbook::STANDARD, 21),
+    spell("Water Surge", Spellbook::STANDARD, 22),
+    spell("Earth Surge", Spellbook::STANDARD, 23),
+    spell("Fire Surge", Spellbook::STANDARD, 24),
+    spell("Crumble Undead", Spellbook::STANDARD, 15),
+    spell("Iban Blast", Spellbook::STANDARD, 25),
+    // Scales with magic level, see `Spell::base_max_hit`
+    spell("Magic Dart", Spellbook::STANDARD, 10),
+    spell("Saradomin Strike", Spellbook::STANDARD, 20),
+    spell("Claws of Guthix", Spellbook::STANDARD, 20),
+    spell("Flames of Zamorak", Spellbook::STANDARD, 20),
+    spell("Smoke Rush", Spellbook::ANCIENT, 13),
+    spell("Shadow Rush", Spellbook::ANCIENT, 14),
+    spell("Blood Rush", Spellbook::ANCIENT, 15),
+    spell("Ice Rush", Spellbook::ANCIENT, 16),
+    spell("Smoke Burst", Spellbook::ANCIENT, 17),
+    spell("Shadow Burst", Spellbook::ANCIENT, 18),
+    spell("Blood Burst", Spellbook::ANCIENT, 21),
+    spell("Ice Burst", Spellbook::ANCIENT, 22),
+    spell("Smoke Blitz", Spellbook::ANCIENT, 23),
+    spell("Shadow Blitz", Spellbook::ANCIENT, 24),
+    spell("Blood Blitz", Spellbook::ANCIENT, 25),
+    spell("Ice Blitz", Spellbook::ANCIENT, 26),
+    spell("Smoke Barrage", Spellbook::ANCIENT, 27),
+    spell("Shadow Barrage", Spellbook::ANCIENT, 28),
+    spell("Blood Barrage", Spellbook::ANCIENT, 29),
+    spell("Ice Barrage", Spellbook::ANCIENT, 30),
+    spell("Ghostly Grasp", Spellbook::ARCEUUS, 12),
+    spell("Skeletal Grasp", Spellbook::ARCEUUS, 17),
+    spell("Undead Grasp", Spellbook::ARCEUUS, 24),
+    spell("Inferior Demonbane", Spellbook::ARCEUUS, 16),
+    spell("Superior Demonbane", Spellbook::ARCEUUS, 23),
+    spell("Dark Demonbane", Spellbook::ARCEUUS, 30),
+];
+
+impl fmt::Display for Spell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}) {}", self.spellbook, self.name)
+    }
+}
+
+impl Spell {
+    pub fn all() -> &'static [Spell] {
+        SPELLS
+    }
+
+    pub fn from_name(name: &str) -> Option<Spell> {
+        SPELLS.iter().find(|x| x.name == name).copied()
+    }
+
+    pub fn base_max_hit(&self, magic_level: isize) -> isize {
+        match self.name {
+            "Magic Dart" => self.base_max_hit + magic_level / 10,
+            _ => self.base_max_hit,
+        }
+    }
+
+    /// Some spells can only be cast while wielding a particular staff.
+    pub fn castable_with(&self, weapon: &str) -> bool {
+        match self.name {
+            "Magic Dart" => {
+                weapon.starts_with("Slayer's staff")
+                    || weapon.starts_with("Staff of the dead")
+                    || weapon.starts_with("Toxic staff of the dead")
+                    || weapon.starts_with("Staff of light")
+                    || weapon.starts_with("Staff of balance")
+            }
+            "Iban Blast" => weapon.starts_with("Iban's staff"),
+            "Saradomin Strike" => {
+                weapon == "Saradomin staff" || weapon.starts_with("Staff of light")
+            }
+            "Claws of Guthix" => {
+                weapon == "Guthix staff"
+                    || weapon == "Void knight mace"
+                    || weapon.starts_with("Staff of balance")
+            }
+            "Flames of Zamorak" => {
+                weapon == "Zamorak staff"
+                    || weapon.starts_with("Staff of the dead")
+                    || weapon.starts_with("Toxic staff of the dead")
+            }
+            _ => true,
+        }
+    }
+}
@@ -3,7 +3,7 @@ use serde::Deserialize;
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufReader, Error, ErrorKind};
-use tracing::{debug, info, instrument};
+use tracing::{debug, instrument};
 
 pub trait Store: std::fmt::Debug {
     fn connect(path: &str) -> Self;
@@ -46,7 +46,7 @@ impl ApiStore {
         .json::<Response<Weapon>>()
         .await?;
 
-        if weapons._items.len() > 0 {
+        if !weapons._items.is_empty() {
             Ok(weapons._items[0].clone())
         } else {
             Err(Box::new(Error::new(
@@ -65,7 +65,7 @@ impl ApiStore {
         .json::<Response<Equipment>>()
         .await?;
 
-        if items._items.len() > 0 {
+        if !items._items.is_empty() {
             Ok(items._items[0].clone())
         } else {
             Err(Box::new(Error::new(
@@ -84,7 +84,7 @@ impl ApiStore {
         .json::<Response<Monster>>()
         .await?;
 
-        if monster._items.len() > 0 {
+        if !monster._items.is_empty() {
             Ok(monster._items[0].clone())
         } else {
             Err(Box::new(Error::new(
@@ -144,14 +144,14 @@ impl Store for FileStore {
         let monsters_tmp: HashMap<String, Monster> =
             serde_json::from_reader(monster_reader).expect("Unable to parse file");
         let monsters_transformed: HashMap<String, Monster> = monsters_tmp
-            .iter()
-            .map(|(_x, y)| (y.name.clone(), y.clone()))
+            .values()
+            .map(|y| (y.name.clone(), y.clone()))
             .collect();
 
         FileStore {
             path: String::from(path),
-            weapons: weapons,
-            items: items,
+            weapons,
+            items,
             monsters: monsters_transformed,
         }
     }